@@ -16,23 +16,47 @@
 
 extern crate self as anchor_gen;
 
+#[cfg(feature = "macros")]
 pub use anchor_generate_cpi_crate::generate_cpi_crate;
+#[cfg(feature = "macros")]
 pub use anchor_generate_cpi_interface::generate_cpi_interface;
 pub use anchor_idl::derive_account_type;
 pub use anchor_idl::derive_event_type;
 pub use anchor_idl::derive_instruction_type;
+pub use anchor_idl::is_cpi_event;
+pub use anchor_idl::serde;
+pub use anchor_idl::serde_as;
+pub use anchor_idl::transaction_error_code;
+pub use anchor_idl::Builder;
 pub use anchor_idl::Decode;
+pub use anchor_idl::DecodeCpiEvent;
+pub use anchor_idl::DecodeError;
+pub use anchor_idl::DecodeRef;
 pub use anchor_idl::DiscrimToName;
+pub use anchor_idl::Encode;
+pub use anchor_idl::LogEvent;
+pub use anchor_idl::LogEvents;
 pub use anchor_idl::NameToDiscrim;
+pub use anchor_idl::TypeDiscriminator;
+pub use anchor_idl::EVENT_IX_TAG_LE;
 
 pub mod prelude {
+    #[cfg(feature = "macros")]
     pub use anchor_generate_cpi_crate::generate_cpi_crate;
+    #[cfg(feature = "macros")]
     pub use anchor_generate_cpi_interface::generate_cpi_interface;
     // pub use anchor_idl::ident_name;
     pub use anchor_idl::derive_account_type;
     pub use anchor_idl::derive_event_type;
     pub use anchor_idl::derive_instruction_type;
+    pub use anchor_idl::is_cpi_event;
     pub use anchor_idl::Decode;
+    pub use anchor_idl::DecodeCpiEvent;
+    pub use anchor_idl::DecodeError;
+    pub use anchor_idl::DecodeRef;
     pub use anchor_idl::DiscrimToName;
+    pub use anchor_idl::Encode;
+    pub use anchor_idl::LogEvent;
+    pub use anchor_idl::LogEvents;
     pub use anchor_idl::NameToDiscrim;
 }
@@ -0,0 +1,84 @@
+//! Generates the source of an Anchor CPI crate from a JSON IDL, so that it can be checked in
+//! rather than expanded by `generate_cpi_crate!` at compile time.
+//!
+//! ```text
+//! anchor-gen generate --idl idl.json --out src/generated.rs --zero-copy TickArray
+//! ```
+
+use std::{fs, path::PathBuf, process};
+
+use anchor_idl::{Builder, GeneratorError, GeneratorResult};
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "anchor-gen", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Writes the source of a CPI crate generated from an IDL.
+    Generate(GenerateArgs),
+}
+
+/// Options of `generate`, which mirror those of `generate_cpi_crate!`.
+#[derive(Args)]
+struct GenerateArgs {
+    /// Path to the IDL.
+    #[arg(long)]
+    idl: PathBuf,
+    /// File to write the source to, rather than standard output.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Zero copy structs.
+    #[arg(long, value_name = "STRUCT", value_delimiter = ',')]
+    zero_copy: Vec<String>,
+    /// `repr(packed)` structs.
+    #[arg(long, value_name = "STRUCT", value_delimiter = ',')]
+    packed: Vec<String>,
+    /// Render all non-signer instruction accounts as `AccountInfo`.
+    #[arg(long)]
+    untyped_accounts: bool,
+    /// Derive `Serialize` and `Deserialize` on generated types.
+    #[arg(long)]
+    serde: bool,
+}
+
+fn main() {
+    let Cli { command } = Cli::parse();
+    let result = match command {
+        Command::Generate(args) => generate(args),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn generate(args: GenerateArgs) -> GeneratorResult<()> {
+    let mut builder = Builder::new()
+        .idl(&args.idl)
+        .zero_copy(args.zero_copy)
+        .packed(args.packed);
+    if args.untyped_accounts {
+        builder = builder.untyped_accounts();
+    }
+    if args.serde {
+        builder = builder.serde();
+    }
+    let source = builder
+        .options()?
+        .to_generator_in("".as_ref())?
+        .generate_source()?;
+    match &args.out {
+        Some(out) => fs::write(out, source).map_err(|err| {
+            GeneratorError::new(format!("could not write {}: {}", out.display(), err))
+        }),
+        None => {
+            print!("{}", source);
+            Ok(())
+        }
+    }
+}
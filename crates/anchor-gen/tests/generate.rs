@@ -0,0 +1,27 @@
+//! Runs `anchor-gen generate` on the IDL of govern-cpi. Its output is checked in as the source of
+//! the govern-generated example, which compiles it.
+
+use std::{fs, process::Command};
+
+#[test]
+fn generate() {
+    let out = format!("{}/govern.rs", env!("CARGO_TARGET_TMPDIR"));
+    let status = Command::new(env!("CARGO_BIN_EXE_anchor-gen"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["generate", "--idl", "../../examples/govern-cpi/idl.json"])
+        .args(["--out", &out])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let source = fs::read_to_string(&out).unwrap();
+    let header = format!(
+        "// Generated from the IDL of govern v0.5.6 by anchor-gen v{}. Do not edit.\n\n",
+        anchor_idl::GEN_VERSION.unwrap()
+    );
+    assert!(source.starts_with(&header));
+    assert!(
+        source == include_str!("../../../examples/govern-generated/src/generated.rs"),
+        "examples/govern-generated/src/generated.rs is out of date, see its README"
+    );
+}
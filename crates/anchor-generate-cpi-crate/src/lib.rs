@@ -15,15 +15,18 @@
 //! More examples can be found in the [examples/](https://github.com/cosmic-lab-inc/anchor-gen/tree/master/examples)
 //! directory.
 
-use anchor_idl::GeneratorOptions;
-use quote::quote;
-use syn::{parse_macro_input, LitStr};
+use anchor_idl::CpiCrateInput;
+use syn::parse_macro_input;
 
 /// Generates an Anchor CPI crate from a JSON file.
 ///
 /// # Arguments
 ///
-/// * `input` - Path to a JSON IDL relative to the crate's the Cargo.toml.
+/// * `input` - Path to a JSON IDL relative to the crate's the Cargo.toml, optionally followed by
+///   the options of [generate_cpi_interface](https://docs.rs/anchor-generate-cpi-interface), e.g.
+///   `generate_cpi_crate!("idl.json", untyped_accounts)`. With `serde`, the generated types and the
+///   `AccountType`, `InstructionType` and `EventType` enums also implement serde's `Serialize` and
+///   `Deserialize`.
 ///
 /// # Examples
 ///
@@ -42,73 +45,25 @@ use syn::{parse_macro_input, LitStr};
 /// ```
 #[proc_macro]
 pub fn generate_cpi_crate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let id_literal = parse_macro_input!(input as LitStr);
-    let opts = GeneratorOptions {
-        idl_path: id_literal.value(),
-        ..Default::default()
+    let input = parse_macro_input!(input as CpiCrateInput);
+    let id_literal = &input.idl_path;
+    let opts = match input.to_options() {
+        Ok(opts) => opts,
+        Err(err) => return err.write_errors().into(),
     };
 
-    let gen = opts.to_generator();
-    let mut ts: proc_macro2::TokenStream = gen.generate_cpi_interface();
-
-    let event_variants = gen.event_types().into_iter().map(|ident| {
-        let variant_name = ident.clone();
-        quote! { #variant_name(#ident) }
-    });
-    if event_variants.len() > 0 {
-        let event_ts = quote! {
-            anchor_gen::derive_event_type!(
-                pub enum EventType {
-                    #(#event_variants,)*
-                }
-            );
-        };
-        ts.extend(event_ts);
-    }
-
-    let acct_variants = gen.account_types().into_iter().map(|ident| {
-        let variant_name = ident.clone();
-        quote! { #variant_name(#ident) }
-    });
-    if acct_variants.len() > 0 {
-        let account_ts = quote! {
-            anchor_gen::derive_account_type!(
-                pub enum AccountType {
-                    #(#acct_variants,)*
-                }
-            );
-        };
-        ts.extend(account_ts);
-    }
-
-    let ix_variants = gen.instruction_types().into_iter().map(|ident| {
-        let variant_name = ident.clone();
-
-        // Construct the path prefix
-        let path_prefix: syn::Path = syn::parse_str("instruction").unwrap();
-
-        // Create a new PathSegment with the input Ident
-        let mut segments = path_prefix.segments.clone();
-        segments.push(syn::PathSegment::from(ident));
-
-        // Combine the path prefix and the Ident
-        let full_path = syn::Path {
-            leading_colon: path_prefix.leading_colon,
-            segments,
-        };
-
-        quote! { #variant_name(#full_path) }
-    });
-    if ix_variants.len() > 0 {
-        let ix_ts = quote! {
-            anchor_gen::derive_instruction_type!(
-                pub enum InstructionType {
-                    #(#ix_variants,)*
-                }
-            );
-        };
-        ts.extend(ix_ts);
+    let gen = match opts.to_generator() {
+        Ok(gen) => gen,
+        Err(err) => {
+            return syn::Error::new(id_literal.span(), err)
+                .to_compile_error()
+                .into()
+        }
+    };
+    match gen.generate_cpi_crate() {
+        Ok(tokens) => tokens.into(),
+        Err(err) => syn::Error::new(id_literal.span(), err)
+            .to_compile_error()
+            .into(),
     }
-
-    ts.into()
 }
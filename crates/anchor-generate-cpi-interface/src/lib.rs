@@ -17,7 +17,8 @@
 use anchor_idl::GeneratorOptions;
 use darling::FromMeta;
 use proc_macro::TokenStream;
-use syn::parse_macro_input;
+use proc_macro2::Span;
+use syn::{parse_macro_input, Meta, NestedMeta};
 
 /// Generates an Anchor CPI crate from a JSON file.
 ///
@@ -49,5 +50,28 @@ pub fn generate_cpi_interface(input: proc_macro::TokenStream) -> TokenStream {
             return TokenStream::from(e.write_errors());
         }
     };
-    parsed.to_generator().generate_cpi_interface().into()
+    match parsed
+        .to_generator()
+        .and_then(|gen| gen.generate_cpi_interface())
+    {
+        Ok(tokens) => tokens.into(),
+        Err(err) => syn::Error::new(idl_path_span(&attr_args), err)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// Span of the `idl_path` literal, to which errors in the IDL are attributed.
+fn idl_path_span(attr_args: &syn::AttributeArgs) -> Span {
+    attr_args
+        .iter()
+        .find_map(|arg| match arg {
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("idl_path") =>
+            {
+                Some(name_value.lit.span())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(Span::call_site)
 }
@@ -1,12 +1,158 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 pub use anchor_syn::idl::types::*;
 use heck::{ToPascalCase, ToSnakeCase};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
+use crate::StructOpts;
+
+/// State accounts which instruction accounts may be typed as.
+#[derive(Clone, Debug, Default)]
+pub struct AccountTypes {
+    /// Names of the state accounts, and whether each is zero copy.
+    accounts: BTreeMap<String, bool>,
+    /// Types of instruction accounts recorded in seeds, keyed by the snake case account name.
+    seed_types: BTreeMap<String, String>,
+    /// Snake case names of the instruction accounts which the instruction may initialize.
+    initialized: BTreeSet<String>,
+}
+
+impl AccountTypes {
+    /// Creates the types from the struct accounts of the IDL.
+    pub fn new(
+        account_defs: &[IdlTypeDefinition],
+        struct_opts: &BTreeMap<String, StructOpts>,
+    ) -> Self {
+        let accounts = account_defs
+            .iter()
+            .filter(|def| matches!(def.ty, IdlTypeDefinitionTy::Struct { .. }))
+            .map(|def| {
+                let opts = struct_opts.get(&def.name).copied().unwrap_or_default();
+                (def.name.clone(), opts.zero_copy)
+            })
+            .collect();
+        AccountTypes {
+            accounts,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the types for the accounts of an instruction, with those recorded in their seeds.
+    ///
+    /// Accounts which are recorded with more than one type are left out.
+    pub fn for_instruction(&self, accounts: &[IdlAccountItem]) -> Self {
+        let mut seed_types: BTreeMap<String, Option<String>> = BTreeMap::new();
+        collect_seed_types(accounts, &mut seed_types);
+        let mut initialized = BTreeSet::new();
+        if has_system_program(accounts) {
+            collect_initialized(accounts, &mut initialized);
+        }
+        AccountTypes {
+            accounts: self.accounts.clone(),
+            seed_types: seed_types
+                .into_iter()
+                .filter_map(|(name, ty)| Some((name, ty?)))
+                .collect(),
+            initialized,
+        }
+    }
+
+    /// Resolves the type of a non-signer instruction account, from the types recorded in seeds or
+    /// else from its name.
+    ///
+    /// Accounts the instruction may initialize stay untyped, as they do not hold their state
+    /// account yet when the instruction is invoked.
+    fn resolve(&self, account: &IdlAccount) -> Option<TokenStream> {
+        let name = account.name.to_snake_case();
+        if self.initialized.contains(&name) {
+            return None;
+        }
+        let ty = self
+            .seed_types
+            .get(&name)
+            .filter(|ty| self.accounts.contains_key(*ty))
+            .cloned()
+            .or_else(|| {
+                let ty = account.name.to_pascal_case();
+                self.accounts.contains_key(&ty).then_some(ty)
+            })?;
+        let ident = format_ident!("{}", ty);
+        if self.accounts[&ty] {
+            Some(quote! { AccountLoader<'info, #ident> })
+        } else {
+            // Boxed, as `Accounts` structs are deserialized on the stack, which large state
+            // accounts such as Drift's `User` would overflow on chain.
+            Some(quote! { Box<Account<'info, #ident>> })
+        }
+    }
+}
+
+fn collect_seed_types(
+    accounts: &[IdlAccountItem],
+    seed_types: &mut BTreeMap<String, Option<String>>,
+) {
+    for item in accounts {
+        match item {
+            IdlAccountItem::IdlAccount(account) => {
+                let seeds = account.pda.iter().flat_map(|pda| &pda.seeds);
+                for seed in seeds {
+                    if let IdlSeed::Account(IdlSeedAccount {
+                        account: Some(ty),
+                        path,
+                        ..
+                    }) = seed
+                    {
+                        let name = path.split('.').next().unwrap_or_default().to_snake_case();
+                        seed_types
+                            .entry(name)
+                            .and_modify(|other| {
+                                if other.as_ref() != Some(ty) {
+                                    *other = None;
+                                }
+                            })
+                            .or_insert_with(|| Some(ty.clone()));
+                    }
+                }
+            }
+            IdlAccountItem::IdlAccounts(inner) => collect_seed_types(&inner.accounts, seed_types),
+        }
+    }
+}
+
+/// Whether the accounts of an instruction include the system program, which creating an account
+/// requires.
+fn has_system_program(accounts: &[IdlAccountItem]) -> bool {
+    accounts.iter().any(|item| match item {
+        IdlAccountItem::IdlAccount(account) => account.name.to_snake_case() == "system_program",
+        IdlAccountItem::IdlAccounts(inner) => has_system_program(&inner.accounts),
+    })
+}
+
+/// Collects the writable non-signer accounts with seeds, which an instruction passing the system
+/// program may initialize as Anchor's `init` does.
+fn collect_initialized(accounts: &[IdlAccountItem], initialized: &mut BTreeSet<String>) {
+    for item in accounts {
+        match item {
+            IdlAccountItem::IdlAccount(account) => {
+                if account.is_mut && !account.is_signer && account.pda.is_some() {
+                    initialized.insert(account.name.to_snake_case());
+                }
+            }
+            IdlAccountItem::IdlAccounts(inner) => collect_initialized(&inner.accounts, initialized),
+        }
+    }
+}
+
 /// Generates a list of [IdlAccountItem]s as a [TokenStream].
+///
+/// Non-signer accounts which resolve to one of `account_types` are typed as `Account` or, if zero
+/// copy, `AccountLoader`. Others, including accounts the instruction may initialize, are
+/// `AccountInfo`.
 pub fn generate_account_fields(
     name: &str,
     accounts: &[IdlAccountItem],
+    account_types: &AccountTypes,
 ) -> (TokenStream, TokenStream) {
     let mut all_structs: Vec<TokenStream> = vec![];
     let all_fields = accounts
@@ -22,9 +168,18 @@ pub fn generate_account_fields(
                 let ty = if info.is_signer {
                     quote! { Signer<'info> }
                 } else {
-                    quote! { AccountInfo<'info> }
+                    account_types
+                        .resolve(info)
+                        .unwrap_or_else(|| quote! { AccountInfo<'info> })
+                };
+                let ty = if info.is_optional.unwrap_or(false) {
+                    quote! { Option<#ty> }
+                } else {
+                    ty
                 };
+                let docs = crate::generate_docs(info.docs.as_deref());
                 quote! {
+                   #docs
                    #annotation
                    pub #acc_name: #ty
                 }
@@ -33,7 +188,8 @@ pub fn generate_account_fields(
                 let field_name = format_ident!("{}{}", name, inner.name.to_snake_case());
                 let sub_name = format!("{}{}", name, inner.name.to_pascal_case());
                 let sub_ident = format_ident!("{}", &sub_name);
-                let (sub_structs, sub_fields) = generate_account_fields(&sub_name, &inner.accounts);
+                let (sub_structs, sub_fields) =
+                    generate_account_fields(&sub_name, &inner.accounts, account_types);
                 all_structs.push(sub_structs);
                 all_structs.push(quote! {
                     #[derive(Accounts)]
@@ -0,0 +1,145 @@
+use std::{env, fs, path::PathBuf};
+
+use darling::util::{Flag, PathList};
+
+use crate::{GeneratorError, GeneratorOptions, GeneratorResult};
+
+/// Generates a CPI crate from a `build.rs` script rather than with `generate_cpi_crate!`.
+///
+/// The source written is that of [Generator::generate_source](crate::Generator::generate_source),
+/// i.e. the expansion of `generate_cpi_crate!` with the same options.
+///
+/// # Examples
+///
+/// In `build.rs`:
+///
+/// ```no_run
+/// anchor_idl::Builder::new()
+///     .idl("idl.json")
+///     .zero_copy(["TickArray"])
+///     .out_file("generated.rs")
+///     .emit()
+///     .unwrap();
+/// ```
+///
+/// In `src/lib.rs`:
+///
+/// ```ignore
+/// include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+/// declare_id!("GjphYQcbP1m3FuDyCTUJf2mUMxKPE3j6feWU1rxvC7Ps");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    idl: Option<PathBuf>,
+    zero_copy: Vec<String>,
+    packed: Vec<String>,
+    untyped_accounts: bool,
+    serde: bool,
+    out_file: Option<PathBuf>,
+}
+
+impl Builder {
+    /// Creates a builder without any options.
+    pub fn new() -> Self {
+        Builder::default()
+    }
+
+    /// Path to the IDL, relative to the manifest of the crate.
+    pub fn idl(mut self, path: impl Into<PathBuf>) -> Self {
+        self.idl = Some(path.into());
+        self
+    }
+
+    /// Adds zero copy structs.
+    pub fn zero_copy<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.zero_copy.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds `repr(packed)` structs.
+    pub fn packed<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.packed.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Render all non-signer instruction accounts as `AccountInfo`, rather than typing those
+    /// which hold a state account.
+    pub fn untyped_accounts(mut self) -> Self {
+        self.untyped_accounts = true;
+        self
+    }
+
+    /// Derive `Serialize` and `Deserialize` on generated types.
+    pub fn serde(mut self) -> Self {
+        self.serde = true;
+        self
+    }
+
+    /// Path of the generated source, relative to `OUT_DIR`.
+    pub fn out_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.out_file = Some(path.into());
+        self
+    }
+
+    /// The options of `generate_cpi_crate!` equivalent to those of the builder.
+    pub fn options(&self) -> GeneratorResult<GeneratorOptions> {
+        let idl = self
+            .idl
+            .as_ref()
+            .ok_or_else(|| GeneratorError::new("no IDL was given"))?;
+        Ok(GeneratorOptions {
+            idl_path: idl.to_string_lossy().into_owned(),
+            zero_copy: path_list(&self.zero_copy)?,
+            packed: path_list(&self.packed)?,
+            untyped_accounts: Flag::from(self.untyped_accounts),
+            serde: Flag::from(self.serde),
+        })
+    }
+
+    /// Writes the generated source to the output file, and tells Cargo to run the build script
+    /// again when the IDL changes.
+    pub fn emit(&self) -> GeneratorResult<()> {
+        let manifest_dir = env_dir("CARGO_MANIFEST_DIR")?;
+        let out_dir = env_dir("OUT_DIR")?;
+        let out_file = self
+            .out_file
+            .as_ref()
+            .ok_or_else(|| GeneratorError::new("no output file was given"))?;
+
+        let opts = self.options()?;
+        let source = opts.to_generator_in(&manifest_dir)?.generate_source()?;
+        let out = out_dir.join(out_file);
+        fs::write(&out, source).map_err(|err| {
+            GeneratorError::new(format!("could not write {}: {}", out.display(), err))
+        })?;
+
+        println!(
+            "cargo:rerun-if-changed={}",
+            manifest_dir.join(&opts.idl_path).display()
+        );
+        Ok(())
+    }
+}
+
+/// A directory set by Cargo when running build scripts.
+fn env_dir(var: &str) -> GeneratorResult<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .ok_or_else(|| GeneratorError::new(format!("{} is not set; is this a build script?", var)))
+}
+
+/// Parses the names of structs.
+fn path_list(names: &[String]) -> GeneratorResult<Option<PathList>> {
+    if names.is_empty() {
+        return Ok(None);
+    }
+    let paths = names
+        .iter()
+        .map(|name| {
+            syn::parse_str::<syn::Path>(name).map_err(|_| {
+                GeneratorError::new(format!("expected the name of a struct, found `{}`", name))
+            })
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    Ok(Some(PathList::new(paths)))
+}
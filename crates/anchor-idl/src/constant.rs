@@ -0,0 +1,277 @@
+use std::{fmt::Display, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_syn::idl::types::{IdlConst, IdlType};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{Expr, ExprArray, Lit, UnOp};
+
+use crate::{GeneratorError, GeneratorResult};
+
+/// Generates a `pub const` item for each constant.
+///
+/// Constants whose type is not supported or whose value cannot be parsed, e.g. expressions such as
+/// `PRICE_PRECISION * 10`, are an error pointing to the constant in the IDL.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{generate_constants, IdlConst, IdlType};
+///
+/// let constant = IdlConst {
+///     name: "MAX_PRICE".to_string(),
+///     ty: IdlType::U64,
+///     value: "1_000".to_string(),
+/// };
+/// let constants = generate_constants(&[constant.clone()]).unwrap();
+/// assert_eq!(constants.to_string(), "pub const MAX_PRICE : u64 = 1000u64 ;");
+///
+/// let err = generate_constants(&[IdlConst {
+///     value: "PRICE_PRECISION * 10".to_string(),
+///     ..constant
+/// }])
+/// .unwrap_err();
+/// assert_eq!(err.path.as_deref(), Some("constants[0].value"));
+/// assert_eq!(
+///     err.message,
+///     "could not parse `PRICE_PRECISION * 10`: expected an integer, found `PRICE_PRECISION * 10`"
+/// );
+/// ```
+pub fn generate_constants(constants: &[IdlConst]) -> GeneratorResult<TokenStream> {
+    let defined = constants
+        .iter()
+        .enumerate()
+        .map(|(i, constant)| {
+            let name = format_ident!("{}", constant.name);
+            let (ty, value) = parse_constant(i, constant)?;
+            Ok(quote! {
+                pub const #name: #ty = #value;
+            })
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    Ok(quote! {
+        #(#defined)*
+    })
+}
+
+/// Parses the type and value of the constant at index `i` in the IDL into Rust tokens.
+pub fn parse_constant(
+    i: usize,
+    constant: &IdlConst,
+) -> GeneratorResult<(TokenStream, TokenStream)> {
+    let ty = constant_type(&constant.ty)
+        .map_err(|err| GeneratorError::at(format!("constants[{}].type", i), err))?;
+    let value = parse_constant_value(&constant.ty, &constant.value).map_err(|err| {
+        GeneratorError::at(
+            format!("constants[{}].value", i),
+            format!("could not parse `{}`: {}", constant.value, err),
+        )
+    })?;
+    Ok((ty, value))
+}
+
+/// Parses the string representation of a constant's value in the IDL into a Rust expression of
+/// its type.
+///
+/// Numbers, booleans, strings, byte arrays, public keys and arrays of these are supported.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{parse_constant_value, IdlType};
+///
+/// let value = parse_constant_value(&IdlType::U64, "1_000_000").unwrap();
+/// assert_eq!(value.to_string(), "1000000u64");
+///
+/// let value = parse_constant_value(&IdlType::Bytes, "[115, 101, 101, 100]").unwrap();
+/// assert_eq!(value.to_string(), "& [115u8 , 101u8 , 101u8 , 100u8]");
+///
+/// assert!(parse_constant_value(&IdlType::U8, "256").is_err());
+/// ```
+pub fn parse_constant_value(ty: &IdlType, value: &str) -> Result<TokenStream, String> {
+    match ty {
+        IdlType::Bytes => {
+            let bytes = match syn::parse_str::<Lit>(value) {
+                Ok(Lit::ByteStr(lit)) => lit.value(),
+                _ => {
+                    let expr = parse_expr(value)?;
+                    parse_array(&IdlType::U8, &expr, None)?
+                        .into_iter()
+                        .map(|byte| parse_int::<u8>(&byte))
+                        .collect::<Result<Vec<_>, _>>()?
+                }
+            };
+            Ok(quote! { &[#(#bytes),*] })
+        }
+        IdlType::String => match syn::parse_str::<Lit>(value) {
+            Ok(Lit::Str(lit)) => Ok(lit.value().into_token_stream()),
+            _ => Err(format!("expected a string literal, found `{}`", value)),
+        },
+        IdlType::PublicKey => {
+            let address = match syn::parse_str::<Expr>(value) {
+                Ok(Expr::Lit(expr)) => match expr.lit {
+                    Lit::Str(lit) => lit.value(),
+                    _ => value.to_string(),
+                },
+                Ok(Expr::Macro(expr)) if expr.mac.path.is_ident("pubkey") => {
+                    match syn::parse2::<Lit>(expr.mac.tokens) {
+                        Ok(Lit::Str(lit)) => lit.value(),
+                        _ => return Err(format!("expected a public key, found `{}`", value)),
+                    }
+                }
+                _ => value.to_string(),
+            };
+            let bytes = Pubkey::from_str(&address)
+                .map_err(|_| format!("expected a public key, found `{}`", value))?
+                .to_bytes();
+            Ok(quote! { Pubkey::new_from_array([#(#bytes),*]) })
+        }
+        _ => parse_constant_expr(ty, &parse_expr(value)?),
+    }
+}
+
+fn parse_expr(value: &str) -> Result<Expr, String> {
+    syn::parse_str::<Expr>(value).map_err(|_| "expected a Rust expression".to_string())
+}
+
+fn parse_constant_expr(ty: &IdlType, expr: &Expr) -> Result<TokenStream, String> {
+    match ty {
+        IdlType::Bool => match expr {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Bool(lit) => Ok(lit.value.into_token_stream()),
+                _ => Err(unexpected("a boolean", expr)),
+            },
+            _ => Err(unexpected("a boolean", expr)),
+        },
+        IdlType::U8 => Ok(parse_int::<u8>(expr)?.into_token_stream()),
+        IdlType::I8 => Ok(parse_int::<i8>(expr)?.into_token_stream()),
+        IdlType::U16 => Ok(parse_int::<u16>(expr)?.into_token_stream()),
+        IdlType::I16 => Ok(parse_int::<i16>(expr)?.into_token_stream()),
+        IdlType::U32 => Ok(parse_int::<u32>(expr)?.into_token_stream()),
+        IdlType::I32 => Ok(parse_int::<i32>(expr)?.into_token_stream()),
+        IdlType::U64 => Ok(parse_int::<u64>(expr)?.into_token_stream()),
+        IdlType::I64 => Ok(parse_int::<i64>(expr)?.into_token_stream()),
+        IdlType::U128 => Ok(parse_int::<u128>(expr)?.into_token_stream()),
+        IdlType::I128 => Ok(parse_int::<i128>(expr)?.into_token_stream()),
+        IdlType::F32 => Ok(parse_float::<f32>(expr)?.into_token_stream()),
+        IdlType::F64 => Ok(parse_float::<f64>(expr)?.into_token_stream()),
+        IdlType::Array(inner, len) => {
+            let values = parse_array(inner, expr, Some(*len))?
+                .iter()
+                .map(|el| parse_constant_value(inner, &el.to_token_stream().to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(quote! { [#(#values),*] })
+        }
+        _ => Err(format!(
+            "constants of type {} are not supported",
+            crate::ty_to_rust_type(ty)
+        )),
+    }
+}
+
+/// Returns the elements of an array expression, e.g. `[1, 2]` or `[0; 2]`.
+fn parse_array(ty: &IdlType, expr: &Expr, len: Option<usize>) -> Result<Vec<Expr>, String> {
+    let expected = format!("an array of {}", crate::ty_to_rust_type(ty));
+    let elems = match expr {
+        Expr::Array(ExprArray { elems, .. }) => elems.iter().cloned().collect::<Vec<_>>(),
+        Expr::Repeat(expr) => {
+            let count = parse_int::<usize>(&expr.len)?;
+            vec![(*expr.expr).clone(); count]
+        }
+        _ => return Err(unexpected(&expected, expr)),
+    };
+    match len {
+        Some(len) if elems.len() != len => {
+            Err(format!("expected {} elements, found {}", len, elems.len()))
+        }
+        _ => Ok(elems),
+    }
+}
+
+fn parse_int<T>(expr: &Expr) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let (negative, lit) = match expr {
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => (true, &*unary.expr),
+        _ => (false, expr),
+    };
+    match lit {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(lit) => {
+                let digits = if negative {
+                    format!("-{}", lit.base10_digits())
+                } else {
+                    lit.base10_digits().to_string()
+                };
+                digits
+                    .parse::<T>()
+                    .map_err(|err| format!("invalid integer `{}`: {}", digits, err))
+            }
+            _ => Err(unexpected("an integer", expr)),
+        },
+        _ => Err(unexpected("an integer", expr)),
+    }
+}
+
+fn parse_float<T>(expr: &Expr) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let (negative, lit) = match expr {
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => (true, &*unary.expr),
+        _ => (false, expr),
+    };
+    let digits = match lit {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Float(lit) => lit.base10_digits(),
+            Lit::Int(lit) => lit.base10_digits(),
+            _ => return Err(unexpected("a number", expr)),
+        },
+        _ => return Err(unexpected("a number", expr)),
+    };
+    let digits = if negative {
+        format!("-{}", digits)
+    } else {
+        digits.to_string()
+    };
+    digits
+        .parse::<T>()
+        .map_err(|err| format!("invalid number `{}`: {}", digits, err))
+}
+
+fn unexpected(expected: &str, expr: &impl ToTokens) -> String {
+    format!("expected {}, found `{}`", expected, expr.to_token_stream())
+}
+
+/// Converts the [IdlType] of a constant to its Rust type.
+pub fn constant_type(ty: &IdlType) -> Result<TokenStream, String> {
+    match ty {
+        IdlType::Bytes => Ok(quote! { &[u8] }),
+        IdlType::String => Ok(quote! { &str }),
+        IdlType::Array(inner, len) => {
+            let inner = constant_type(inner)?;
+            Ok(quote! { [#inner; #len] })
+        }
+        IdlType::Bool
+        | IdlType::U8
+        | IdlType::I8
+        | IdlType::U16
+        | IdlType::I16
+        | IdlType::U32
+        | IdlType::I32
+        | IdlType::F32
+        | IdlType::U64
+        | IdlType::I64
+        | IdlType::F64
+        | IdlType::U128
+        | IdlType::I128
+        | IdlType::PublicKey => Ok(crate::ty_to_rust_type(ty).parse().unwrap()),
+        _ => Err(format!(
+            "constants of type {} are not supported",
+            crate::ty_to_rust_type(ty)
+        )),
+    }
+}
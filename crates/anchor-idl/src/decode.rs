@@ -1,5 +1,8 @@
+use std::{collections::BTreeMap, fmt, io};
+
 use anchor_lang::solana_program::hash::hash;
-use heck::{ToPascalCase, ToSnakeCase};
+use bytemuck::{Pod, PodCastError};
+use heck::ToSnakeCase;
 
 pub fn ident_name<'a, T: ?Sized + 'a>() -> String {
     let full_ident_name = std::any::type_name::<T>();
@@ -9,26 +12,266 @@ pub fn ident_name<'a, T: ?Sized + 'a>() -> String {
     }
 }
 
+/// An error encountered while decoding an account, instruction or event, or while looking up a
+/// discriminator, in the types derived by the `derive_*_type!` macros.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The data is shorter than the discriminator of every variant.
+    TooShort {
+        /// Length of the data.
+        len: usize,
+    },
+    /// The data does not start with the discriminator of any variant.
+    ///
+    /// Holds the prefix of the data as long as the longest known discriminator, or the whole data
+    /// if it is shorter. As discriminators may be of any length, this is not necessarily the
+    /// discriminator the data was written with, e.g. when a variant is missing from the IDL.
+    UnknownDiscriminator(Vec<u8>),
+    /// The data following the discriminator could not be deserialized.
+    Borsh(io::Error),
+    /// The data following the discriminator of a zero copy account is too short, or is not
+    /// aligned for a borrowed account.
+    Pod(PodCastError),
+    /// The data was deserialized, but bytes remain.
+    TrailingBytes {
+        /// Number of bytes which were not read.
+        remaining: usize,
+    },
+    /// No variant has the given name.
+    UnknownName(String),
+    /// The data of a log line is not valid base64.
+    Base64(base64::DecodeError),
+}
+
+impl DecodeError {
+    /// Error for data which starts with none of the discriminators, the shortest and longest of
+    /// which are `shortest` and `longest` bytes long.
+    #[doc(hidden)]
+    pub fn __unmatched(data: &[u8], shortest: usize, longest: usize) -> Self {
+        if data.len() < shortest {
+            DecodeError::TooShort { len: data.len() }
+        } else {
+            DecodeError::UnknownDiscriminator(data[..longest.min(data.len())].to_vec())
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort { len } => {
+                write!(f, "data of {} bytes is shorter than any discriminator", len)
+            }
+            DecodeError::UnknownDiscriminator(discrim) => {
+                write!(f, "unknown discriminator {:?}", discrim)
+            }
+            DecodeError::Borsh(err) => write!(f, "could not deserialize data: {}", err),
+            DecodeError::Pod(err) => write!(f, "could not cast data: {:?}", err),
+            DecodeError::TrailingBytes { remaining } => {
+                write!(f, "{} bytes remain after deserializing data", remaining)
+            }
+            DecodeError::UnknownName(name) => write!(f, "unknown name {}", name),
+            DecodeError::Base64(err) => write!(f, "invalid base64 data: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Borsh(err) => Some(err),
+            DecodeError::Base64(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> Self {
+        DecodeError::Borsh(err)
+    }
+}
+
+impl From<PodCastError> for DecodeError {
+    fn from(err: PodCastError) -> Self {
+        DecodeError::Pod(err)
+    }
+}
+
 pub trait Decode: Sized {
     /// Deserialize a program account into its defined (struct) type using Borsh.
     /// utf8 discriminator is the human-readable discriminator, such as "User", and usually the name
     /// of the struct marked with the #[account] Anchor macro that derives the Discriminator trait.
-    fn decode(data: &[u8]) -> std::result::Result<Self, Box<dyn std::error::Error>>;
+    ///
+    /// The types derived by the `derive_*_type!` macros never panic while decoding, whatever the
+    /// data: data which is too short or malformed is reported as a [DecodeError].
+    fn decode(data: &[u8]) -> std::result::Result<Self, DecodeError>;
+}
+
+/// Borrows a zero copy account from its raw data without copying it.
+///
+/// Implemented for every zero copy account with a [TypeDiscriminator], i.e. accounts marked
+/// `#[zero_copy]` in `derive_account_type!`. As with Anchor's `AccountLoader`, bytes following the
+/// account are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{derive_account_type, Decode, DecodeError, DecodeRef, Encode};
+/// use anchor_lang::prelude::*;
+///
+/// declare_id!("GjphYQcbP1m3FuDyCTUJf2mUMxKPE3j6feWU1rxvC7Ps");
+///
+/// #[account(zero_copy)]
+/// pub struct Tick {
+///     pub liquidity: u64,
+///     pub initialized: u8,
+///     pub padding: [u8; 7],
+/// }
+///
+/// derive_account_type!(
+///     pub enum AccountType {
+///         #[zero_copy]
+///         Tick(Tick) = [1, 2, 3, 4, 5, 6, 7, 8],
+///     }
+/// );
+///
+/// fn main() {
+///     // Account data is 8 byte aligned, as it is on chain.
+///     let mut words = [0u64; 3];
+///     words[0] = u64::from_le_bytes([1, 2, 3, 4, 5, 6, 7, 8]);
+///     words[1] = 7;
+///     let data: &[u8] = bytemuck::cast_slice(&words);
+///
+///     assert_eq!(Tick::decode_ref(data).unwrap().liquidity, 7);
+///     match AccountType::decode(data).unwrap() {
+///         AccountType::Tick(tick) => assert_eq!(tick.liquidity, 7),
+///     }
+///     assert_eq!(AccountType::decode(data).unwrap().encode(), &data[..24]);
+///     assert!(matches!(
+///         Tick::decode_ref(&data[..16]),
+///         Err(DecodeError::Pod(_))
+///     ));
+/// }
+/// ```
+pub trait DecodeRef: TypeDiscriminator + Pod {
+    /// Borrows the account following its discriminator in `data`, which must be aligned for it.
+    fn decode_ref(data: &[u8]) -> std::result::Result<&Self, DecodeError> {
+        let rest = data.strip_prefix(Self::DISCRIMINATOR).ok_or_else(|| {
+            DecodeError::__unmatched(data, Self::DISCRIMINATOR.len(), Self::DISCRIMINATOR.len())
+        })?;
+        let bytes = rest
+            .get(..std::mem::size_of::<Self>())
+            .ok_or(PodCastError::SizeMismatch)?;
+        Ok(bytemuck::try_from_bytes(bytes)?)
+    }
+}
+
+impl<T: TypeDiscriminator + Pod> DecodeRef for T {}
+
+/// Copies a zero copy account out of the data following its discriminator, which need not be
+/// aligned.
+#[doc(hidden)]
+pub fn __decode_zero_copy<T: Pod>(data: &[u8]) -> std::result::Result<T, DecodeError> {
+    let bytes = data
+        .get(..std::mem::size_of::<T>())
+        .ok_or(PodCastError::SizeMismatch)?;
+    Ok(bytemuck::try_pod_read_unaligned(bytes)?)
+}
+
+/// Serializes an account, instruction or event into the data it is decoded from with [Decode].
+///
+/// Implemented by the `derive_*_type!` macros.
+pub trait Encode {
+    /// Serializes into the discriminator of the variant followed by its data, using Borsh or, for
+    /// zero copy accounts, their bytes.
+    ///
+    /// Decoding the result with [Decode::decode] returns the same value.
+    fn encode(&self) -> Vec<u8>;
+}
+
+/// Appends the bytes of a zero copy account to `buf`.
+#[doc(hidden)]
+pub fn __encode_zero_copy<T: Pod>(value: &T, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(bytemuck::bytes_of(value));
+}
+
+/// Tag which prefixes the data of the instruction a program invokes on itself to emit an event with
+/// Anchor's `emit_cpi!`, as `anchor_lang::event::EVENT_IX_TAG_LE`.
+pub const EVENT_IX_TAG_LE: [u8; 8] = 0x1d9acb512ea545e4u64.to_le_bytes();
+
+/// Whether the data of an instruction is an event emitted with `emit_cpi!` rather than an
+/// instruction of the program.
+///
+/// Such instructions are invoked by the program on itself, so they should only be checked if the
+/// inner instruction's program ID is that of the program.
+pub fn is_cpi_event(data: &[u8]) -> bool {
+    data.starts_with(&EVENT_IX_TAG_LE)
+}
+
+/// Decodes events emitted with Anchor's `emit_cpi!`, which are recorded as inner instructions
+/// rather than in the logs, so they can be recovered when the logs are truncated.
+///
+/// Implemented by `derive_event_type!`.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{derive_event_type, is_cpi_event, DecodeCpiEvent, DecodeError, Encode};
+/// use anchor_lang::prelude::*;
+///
+/// declare_id!("GjphYQcbP1m3FuDyCTUJf2mUMxKPE3j6feWU1rxvC7Ps");
+///
+/// #[event]
+/// pub struct Incremented {
+///     pub count: u64,
+/// }
+///
+/// derive_event_type!(
+///     pub enum EventType {
+///         Incremented(Incremented),
+///     }
+/// );
+///
+/// fn main() {
+///     let event = EventType::Incremented(Incremented { count: 7 }).encode();
+///     let mut data = anchor_idl::EVENT_IX_TAG_LE.to_vec();
+///     data.extend_from_slice(&event);
+///
+///     assert!(is_cpi_event(&data));
+///     match EventType::decode_cpi_event(&data).unwrap() {
+///         EventType::Incremented(event) => assert_eq!(event.count, 7),
+///     }
+///     assert!(!is_cpi_event(&event));
+///     assert!(matches!(
+///         EventType::decode_cpi_event(&event),
+///         Err(DecodeError::UnknownDiscriminator(_))
+///     ));
+/// }
+/// ```
+pub trait DecodeCpiEvent: Decode {
+    /// Decodes the data of an instruction emitted by `emit_cpi!`: the [EVENT_IX_TAG_LE] followed
+    /// by the event's discriminator and data.
+    fn decode_cpi_event(data: &[u8]) -> std::result::Result<Self, DecodeError> {
+        let event = data.strip_prefix(&EVENT_IX_TAG_LE[..]).ok_or_else(|| {
+            DecodeError::__unmatched(data, EVENT_IX_TAG_LE.len(), EVENT_IX_TAG_LE.len())
+        })?;
+        Self::decode(event)
+    }
 }
 
 pub trait NameToDiscrim: Sized {
     /// Deserialize a program account into its defined (struct) type using Borsh.
     /// utf8 discriminator is the human-readable discriminator, such as "User", and usually the name
     /// of the struct marked with the #[account] Anchor macro that derives the Discriminator trait.
-    fn name_to_discrim(name: &str) -> std::result::Result<[u8; 8], Box<dyn std::error::Error>>;
+    fn name_to_discrim(name: &str) -> std::result::Result<Vec<u8>, DecodeError>;
 }
 
 pub trait DiscrimToName: Sized {
     /// Deserialize a program account into its defined (struct) type using Borsh.
     /// utf8 discriminator is the human-readable discriminator, such as "User", and usually the name
     /// of the struct marked with the #[account] Anchor macro that derives the Discriminator trait.
-    fn discrim_to_name(discrim: [u8; 8])
-        -> std::result::Result<String, Box<dyn std::error::Error>>;
+    fn discrim_to_name(discrim: &[u8]) -> std::result::Result<String, DecodeError>;
 }
 
 /// Derives the account discriminator from the account name as Anchor does.
@@ -61,59 +304,432 @@ pub fn event_discriminator(name: &str) -> [u8; 8] {
     discriminator
 }
 
+/// Discriminators recorded in an IDL, keyed by the name of the account, instruction or event.
+///
+/// IDLs following the Anchor 0.30 specification record the discriminator of every account,
+/// instruction and event, which may be custom and of any length. Legacy IDLs do not, in which
+/// case the discriminator is derived from the name as Anchor does.
+#[derive(Clone, Debug, Default)]
+pub struct IdlDiscriminators {
+    pub accounts: BTreeMap<String, Vec<u8>>,
+    pub instructions: BTreeMap<String, Vec<u8>>,
+    pub events: BTreeMap<String, Vec<u8>>,
+}
+
+impl IdlDiscriminators {
+    /// Discriminator of the account with the given name.
+    pub fn account(&self, name: &str) -> Vec<u8> {
+        self.accounts
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| account_discriminator(name).to_vec())
+    }
+
+    /// Discriminator of the instruction with the given name.
+    pub fn instruction(&self, name: &str) -> Vec<u8> {
+        self.instructions
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| instruction_discriminator(name).to_vec())
+    }
+
+    /// Discriminator of the event with the given name.
+    pub fn event(&self, name: &str) -> Vec<u8> {
+        self.events
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| event_discriminator(name).to_vec())
+    }
+}
+
+/// A type whose discriminator is known at compile time.
+///
+/// Implemented by the `derive_*_type!` macros for the type of every variant given an explicit
+/// discriminator, which `generate_cpi_crate!` always provides. Decoding then matches the data
+/// against the bytes of the discriminators rather than hashing the name of each type.
+///
+/// As the trait is implemented for the types of the variants, explicit discriminators can only be
+/// given to types defined in the crate invoking the macro. Variants of foreign types must leave
+/// them out.
+///
+/// Note that types generated by Anchor also implement `anchor_lang::Discriminator`, so this trait
+/// is not part of the `anchor_gen` prelude.
+pub trait TypeDiscriminator {
+    /// Discriminator which prefixes the serialized data of the type.
+    const DISCRIMINATOR: &'static [u8];
+}
+
+/// Implements [TypeDiscriminator] for the type of a variant in the `derive_*_type!` macros if it
+/// has an explicit discriminator.
+#[doc(hidden)]
 #[macro_export]
-macro_rules! derive_account_type {
-    ($vis:vis enum $ident:ident {
-        $($variant:ident ($account_type:ty)),*$(,)?
-    }) => {
+macro_rules! __impl_discriminator {
+    ($ty:ty, [$($discrim:literal),*]) => {
+        impl $crate::TypeDiscriminator for $ty {
+            const DISCRIMINATOR: &'static [u8] = &[$($discrim),*];
+        }
+    };
+    ($ty:ty) => {};
+}
+
+/// Resolves the discriminator of a variant in the `derive_*_type!` macros as a `&'static [u8]`:
+/// the explicit bytes if provided, otherwise the discriminator derived from the name of the type,
+/// which is computed once.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __discriminator {
+    ($kind:ident, $ty:ty, [$($discrim:literal),*]) => {
+        &[$($discrim),*][..]
+    };
+    ($kind:ident, $ty:ty) => {{
+        static DISCRIMINATOR: std::sync::OnceLock<[u8; 8]> = std::sync::OnceLock::new();
+        &DISCRIMINATOR.get_or_init(|| $crate::__discriminator!(@derive $kind, $ty))[..]
+    }};
+    (@derive account, $ty:ty) => {
+        $crate::account_discriminator(&$crate::ident_name::<$ty>())
+    };
+    (@derive instruction, $ty:ty) => {
+        $crate::instruction_discriminator(&$crate::ident_name::<$ty>())
+    };
+    (@derive event, $ty:ty) => {
+        $crate::event_discriminator(&$crate::ident_name::<$ty>())
+    };
+}
+
+/// Pattern of a `match` on data in the `derive_*_type!` macros, which matches data starting with
+/// the discriminator of a variant if it is explicit, binding `$rest` to the bytes following it.
+/// Such arms are compiled by rustc into a decision tree on the bytes of the data, so that the
+/// cost of decoding does not grow with the number of variants.
+///
+/// Derived discriminators are only known at run time, so the pattern binds `$rest` to all of the
+/// data, which is checked by `__discriminator_guard!` and stripped by `__discriminator_rest!`.
+///
+/// With `@exact`, the pattern matches the discriminator alone.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __discriminator_pattern {
+    ($rest:ident, [$($discrim:literal),*]) => {
+        [$($discrim,)* $rest @ ..]
+    };
+    ($rest:ident) => {
+        $rest
+    };
+    (@exact $data:ident, [$($discrim:literal),*]) => {
+        [$($discrim),*]
+    };
+    (@exact $data:ident) => {
+        $data
+    };
+}
+
+/// Guard of an arm matching `__discriminator_pattern!`, which checks derived discriminators.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __discriminator_guard {
+    ($kind:ident, $ty:ty, $rest:ident, [$($discrim:literal),*]) => {
+        true
+    };
+    ($kind:ident, $ty:ty, $rest:ident) => {
+        $rest.starts_with($crate::__discriminator!($kind, $ty))
+    };
+    (@exact $kind:ident, $ty:ty, $data:ident, [$($discrim:literal),*]) => {
+        true
+    };
+    (@exact $kind:ident, $ty:ty, $data:ident) => {
+        $data == $crate::__discriminator!($kind, $ty)
+    };
+}
+
+/// Data following the discriminator in an arm matching `__discriminator_pattern!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __discriminator_rest {
+    ($kind:ident, $ty:ty, $rest:ident, [$($discrim:literal),*]) => {
+        $rest
+    };
+    ($kind:ident, $ty:ty, $rest:ident) => {
+        &$rest[$crate::__discriminator!($kind, $ty).len()..]
+    };
+}
+
+/// Error for data which starts with none of the discriminators of the `derive_*_type!` macros.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __unmatched {
+    ($data:expr, $($discrim:expr),*) => {{
+        let discrims: &[&[u8]] = &[$($discrim),*];
+        let shortest = discrims.iter().map(|discrim| discrim.len()).min().unwrap_or(0);
+        let longest = discrims.iter().map(|discrim| discrim.len()).max().unwrap_or(0);
+        $crate::DecodeError::__unmatched($data, shortest, longest)
+    }};
+}
+
+/// Defines the enum of `derive_account_type!`, which only derives Borsh if no account is zero
+/// copy, as zero copy accounts do not implement it.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __account_type_enum {
+    ([] $(#[$attr:meta])* $vis:vis enum $ident:ident { $($variant:ident ($account_type:ty)),* }) => {
         #[repr(C)]
         #[derive(Clone)]
         #[derive(anchor_lang::prelude::AnchorDeserialize, anchor_lang::prelude::AnchorSerialize)]
+        $(#[$attr])*
+        $vis enum $ident {
+            $($variant($account_type),)*
+        }
+    };
+    ([$($zero_copy:ident)+] $(#[$attr:meta])* $vis:vis enum $ident:ident { $($variant:ident ($account_type:ty)),* }) => {
+        #[repr(C)]
+        #[derive(Clone)]
+        $(#[$attr])*
         $vis enum $ident {
             $($variant($account_type),)*
         }
+    };
+}
+
+/// Decodes an account from the data following its discriminator in `derive_account_type!`:
+/// zero copy accounts are cast, others are deserialized with Borsh.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __decode_account {
+    (zero_copy, $account_type:ty, $data:expr) => {
+        $crate::__decode_zero_copy::<$account_type>($data)
+    };
+    (, $account_type:ty, $data:expr) => {{
+        let mut rest: &[u8] = $data;
+        match <$account_type as anchor_lang::prelude::AnchorDeserialize>::deserialize(&mut rest) {
+            Ok(_) if !rest.is_empty() => Err($crate::DecodeError::TrailingBytes {
+                remaining: rest.len(),
+            }),
+            Ok(acct) => Ok(acct),
+            Err(err) => Err($crate::DecodeError::Borsh(err)),
+        }
+    }};
+}
+
+/// Appends the data of an account to `buf` in `derive_account_type!`: the bytes of zero copy
+/// accounts, the Borsh serialization of others.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __encode_account {
+    (zero_copy, $acct:expr, $buf:expr) => {
+        $crate::__encode_zero_copy($acct, $buf)
+    };
+    (, $acct:expr, $buf:expr) => {
+        $crate::__encode_borsh!($acct, $buf)
+    };
+}
+
+/// Appends the Borsh serialization of `$value` to the `Vec<u8>` `$buf`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __encode_borsh {
+    ($value:expr, $buf:expr) => {
+        anchor_lang::prelude::AnchorSerialize::serialize($value, $buf)
+            .expect("writing to a Vec cannot fail")
+    };
+}
+
+/// Derives an enum of the accounts of a program, which can be decoded from raw account data.
+///
+/// Each variant may be followed by the discriminator recorded in the IDL, in which case the type of
+/// the variant implements [TypeDiscriminator], so it must be defined in the invoking crate. Variants
+/// without one use the discriminator Anchor derives from the name of the account.
+///
+/// Decoding dispatches on the bytes of the data with a `match` whose arms are the explicit
+/// discriminators, so it takes the same time whatever the number of variants.
+///
+/// Variants of zero copy accounts are marked `#[zero_copy]` and decoded by casting their data
+/// rather than with Borsh, in which case the enum does not implement Borsh. See [DecodeRef] to
+/// borrow them instead. Any other attribute on a variant is an error.
+///
+/// Attributes of the enum, such as further derives, are passed through to it.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{
+///     derive_account_type, Decode, DecodeError, DiscrimToName, NameToDiscrim, TypeDiscriminator,
+/// };
+/// use anchor_lang::prelude::*;
+///
+/// declare_id!("GjphYQcbP1m3FuDyCTUJf2mUMxKPE3j6feWU1rxvC7Ps");
+///
+/// #[account]
+/// pub struct Counter {
+///     pub count: u64,
+/// }
+///
+/// #[account]
+/// pub struct Config {
+///     pub admin: Pubkey,
+/// }
+///
+/// derive_account_type!(
+///     pub enum AccountType {
+///         Counter(Counter) = [1, 2, 3, 4],
+///         Config(Config),
+///     }
+/// );
+///
+/// fn main() {
+///     assert_eq!(<Counter as TypeDiscriminator>::DISCRIMINATOR, &[1, 2, 3, 4]);
+///     assert_eq!(AccountType::name_to_discrim("Counter").unwrap(), vec![1, 2, 3, 4]);
+///     assert_eq!(AccountType::discrim_to_name(&[1, 2, 3, 4]).unwrap(), "Counter");
+///     assert_eq!(
+///         AccountType::name_to_discrim("Config").unwrap(),
+///         anchor_idl::account_discriminator("Config").to_vec()
+///     );
+///
+///     let mut data = vec![1, 2, 3, 4];
+///     data.extend_from_slice(&7u64.to_le_bytes());
+///     match AccountType::decode(&data).unwrap() {
+///         AccountType::Counter(counter) => assert_eq!(counter.count, 7),
+///         AccountType::Config(_) => unreachable!(),
+///     }
+///
+///     assert!(matches!(
+///         AccountType::decode(&[1, 2]),
+///         Err(DecodeError::TooShort { len: 2 })
+///     ));
+///     assert!(matches!(
+///         AccountType::decode(&[9; 16]),
+///         Err(DecodeError::UnknownDiscriminator(discrim)) if discrim == vec![9; 8]
+///     ));
+///     assert!(matches!(
+///         AccountType::decode(&data[..6]),
+///         Err(DecodeError::Borsh(_))
+///     ));
+///     data.push(0);
+///     assert!(matches!(
+///         AccountType::decode(&data),
+///         Err(DecodeError::TrailingBytes { remaining: 1 })
+///     ));
+///     assert!(matches!(
+///         AccountType::name_to_discrim("Vault"),
+///         Err(DecodeError::UnknownName(name)) if name == "Vault"
+///     ));
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use anchor_idl::derive_account_type;
+/// use anchor_lang::prelude::*;
+///
+/// declare_id!("GjphYQcbP1m3FuDyCTUJf2mUMxKPE3j6feWU1rxvC7Ps");
+///
+/// #[account(zero_copy)]
+/// pub struct Tick {
+///     pub liquidity: u64,
+/// }
+///
+/// derive_account_type!(
+///     pub enum AccountType {
+///         #[zerocopy]
+///         Tick(Tick) = [1, 2, 3, 4, 5, 6, 7, 8],
+///     }
+/// );
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! derive_account_type {
+    ($(#[$attr:meta])* $vis:vis enum $ident:ident { $($variants:tt)* }) => {
+        $crate::__derive_account_type!(@variants [$(#[$attr])* $vis enum $ident] [] $($variants)*);
+    };
+}
+
+/// Implements `derive_account_type!`. Its variants are first rewritten one at a time, so that the
+/// `#[zero_copy]` marker is matched literally and any other attribute on a variant is rejected.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __derive_account_type {
+    (@variants $head:tt [$($done:tt)*]
+        #[zero_copy] $variant:ident ($account_type:ty) $(= [$($discrim:literal),*$(,)?])? $(, $($rest:tt)*)?
+    ) => {
+        $crate::__derive_account_type!(
+            @variants $head [$($done)* {[zero_copy] $variant ($account_type) $([$($discrim),*])?}] $($($rest)*)?
+        );
+    };
+    (@variants $head:tt [$($done:tt)*]
+        $variant:ident ($account_type:ty) $(= [$($discrim:literal),*$(,)?])? $(, $($rest:tt)*)?
+    ) => {
+        $crate::__derive_account_type!(
+            @variants $head [$($done)* {[] $variant ($account_type) $([$($discrim),*])?}] $($($rest)*)?
+        );
+    };
+    (@variants $head:tt [$($done:tt)*]) => {
+        $crate::__derive_account_type!(@impl $head $($done)*);
+    };
+    (@impl [$(#[$attr:meta])* $vis:vis enum $ident:ident]
+        $({[$($zero_copy:ident)?] $variant:ident ($account_type:ty) $([$($discrim:literal),*])?})*
+    ) => {
+        $crate::__account_type_enum!(
+            [$($($zero_copy)?)*] $(#[$attr])* $vis enum $ident { $($variant($account_type)),* }
+        );
+
+        $($crate::__impl_discriminator!($account_type$(, [$($discrim),*])?);)*
 
         impl $crate::Decode for $ident {
-          fn decode(data: &[u8]) -> std::result::Result<Self, Box<dyn std::error::Error>> {
-            let discrim: &[u8; 8] = data[..8].try_into().map_err(|e| {
-              Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Instruction data is not 8 bytes or more".to_string()))
-            })?;
-            match discrim {
+          fn decode(data: &[u8]) -> std::result::Result<Self, $crate::DecodeError> {
+            match data {
               $(
-                $variant if discrim == &$crate::account_discriminator(&$crate::ident_name::<$account_type>()) => {
-                    let acct = <$account_type>::try_from_slice(&data[8..])?;
-                    Ok(Self::$variant(acct.clone()))
+                $crate::__discriminator_pattern!(rest$(, [$($discrim),*])?)
+                    if $crate::__discriminator_guard!(account, $account_type, rest$(, [$($discrim),*])?) =>
+                {
+                    let rest = $crate::__discriminator_rest!(account, $account_type, rest$(, [$($discrim),*])?);
+                    let acct = $crate::__decode_account!($($zero_copy)?, $account_type, rest)?;
+                    Ok(Self::$variant(acct))
                 },
               )*
-              _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid account discriminator".to_string())))
+              _ => Err($crate::__unmatched!(
+                  data,
+                  $($crate::__discriminator!(account, $account_type$(, [$($discrim),*])?)),*
+              )),
             }
           }
         }
 
+        impl $crate::Encode for $ident {
+            fn encode(&self) -> Vec<u8> {
+                match self {
+                    $(
+                      Self::$variant(value) => {
+                          let mut data = $crate::__discriminator!(account, $account_type$(, [$($discrim),*])?).to_vec();
+                          $crate::__encode_account!($($zero_copy)?, value, &mut data);
+                          data
+                      },
+                    )*
+                }
+            }
+        }
+
         impl $crate::NameToDiscrim for $ident {
-            fn name_to_discrim(name: &str) -> std::result::Result<[u8; 8], Box<dyn std::error::Error>> {
+            fn name_to_discrim(name: &str) -> std::result::Result<Vec<u8>, $crate::DecodeError> {
                 match name {
                     $(
-                      $variant if name == $crate::ident_name::<$account_type>() => {
-                          let discrim = $crate::account_discriminator(&$crate::ident_name::<$account_type>());
-                          Ok(discrim)
+                      _ if name == $crate::ident_name::<$account_type>() => {
+                          let discrim = $crate::__discriminator!(account, $account_type$(, [$($discrim),*])?);
+                          Ok(discrim.to_vec())
                       },
                     )*
-                    _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid account name".to_string())))
+                    _ => Err($crate::DecodeError::UnknownName(name.to_string()))
                 }
             }
         }
 
         impl $crate::DiscrimToName for $ident {
-            fn discrim_to_name(discrim: [u8; 8]) -> std::result::Result<String, Box<dyn std::error::Error>> {
+            fn discrim_to_name(discrim: &[u8]) -> std::result::Result<String, $crate::DecodeError> {
                 match discrim {
                     $(
-                      $variant if discrim == $crate::account_discriminator(&$crate::ident_name::<$account_type>()) => {
+                      $crate::__discriminator_pattern!(@exact discrim$(, [$($discrim),*])?)
+                          if $crate::__discriminator_guard!(@exact account, $account_type, discrim$(, [$($discrim),*])?) =>
+                      {
                           let name = $crate::ident_name::<$account_type>();
                           Ok(name)
                       },
                     )*
-                    _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid account discriminator".to_string())))
+                    _ => Err($crate::DecodeError::UnknownDiscriminator(discrim.to_vec()))
                 }
             }
         }
@@ -122,55 +738,77 @@ macro_rules! derive_account_type {
 
 #[macro_export]
 macro_rules! derive_instruction_type {
-    ($vis:vis enum $ident:ident {
-        $($variant:ident($ix_type:path)),*$(,)?
+    ($(#[$attr:meta])* $vis:vis enum $ident:ident {
+        $($variant:ident ($ix_type:path) $(= [$($discrim:literal),*$(,)?])?),*$(,)?
     }) => {
         #[derive(anchor_lang::prelude::AnchorSerialize, anchor_lang::prelude::AnchorDeserialize)]
+        $(#[$attr])*
         $vis enum $ident {
             $($variant($ix_type),)*
         }
 
+        $($crate::__impl_discriminator!($ix_type$(, [$($discrim),*])?);)*
+
         impl $crate::Decode for $ident {
-          fn decode(data: &[u8]) -> std::result::Result<Self, Box<dyn std::error::Error>> {
-            let discrim: &[u8; 8] = data[..8].try_into().map_err(|e| {
-              Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Instruction data is not 8 bytes or more".to_string()))
-            })?;
-            match discrim {
-                $(
-                  $variant if discrim == &$crate::instruction_discriminator(&$crate::ident_name::<$ix_type>()) => {
-                      let ix = <$ix_type>::deserialize(&mut &data[8..])?;
-                       Ok(Self::$variant(ix))
-                  },
-                )*
-                _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid instruction discriminator".to_string())))
+          fn decode(data: &[u8]) -> std::result::Result<Self, $crate::DecodeError> {
+            match data {
+              $(
+                $crate::__discriminator_pattern!(rest$(, [$($discrim),*])?)
+                    if $crate::__discriminator_guard!(instruction, $ix_type, rest$(, [$($discrim),*])?) =>
+                {
+                    let mut rest = $crate::__discriminator_rest!(instruction, $ix_type, rest$(, [$($discrim),*])?);
+                    let ix = <$ix_type as anchor_lang::prelude::AnchorDeserialize>::deserialize(&mut rest)?;
+                    Ok(Self::$variant(ix))
+                },
+              )*
+              _ => Err($crate::__unmatched!(
+                  data,
+                  $($crate::__discriminator!(instruction, $ix_type$(, [$($discrim),*])?)),*
+              )),
             }
           }
         }
 
+        impl $crate::Encode for $ident {
+            fn encode(&self) -> Vec<u8> {
+                match self {
+                    $(
+                      Self::$variant(value) => {
+                          let mut data = $crate::__discriminator!(instruction, $ix_type$(, [$($discrim),*])?).to_vec();
+                          $crate::__encode_borsh!(value, &mut data);
+                          data
+                      },
+                    )*
+                }
+            }
+        }
+
         impl $crate::NameToDiscrim for $ident {
-            fn name_to_discrim(name: &str) -> std::result::Result<[u8; 8], Box<dyn std::error::Error>> {
+            fn name_to_discrim(name: &str) -> std::result::Result<Vec<u8>, $crate::DecodeError> {
                 match name {
                     $(
-                      $variant if name == $crate::ident_name::<$ix_type>() => {
-                          let discrim = $crate::instruction_discriminator(&$crate::ident_name::<$ix_type>());
-                          Ok(discrim)
+                      _ if name == $crate::ident_name::<$ix_type>() => {
+                          let discrim = $crate::__discriminator!(instruction, $ix_type$(, [$($discrim),*])?);
+                          Ok(discrim.to_vec())
                       },
                     )*
-                    _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid instruction name".to_string())))
+                    _ => Err($crate::DecodeError::UnknownName(name.to_string()))
                 }
             }
         }
 
         impl $crate::DiscrimToName for $ident {
-            fn discrim_to_name(discrim: [u8; 8]) -> std::result::Result<String, Box<dyn std::error::Error>> {
+            fn discrim_to_name(discrim: &[u8]) -> std::result::Result<String, $crate::DecodeError> {
                 match discrim {
                     $(
-                      $variant if discrim == $crate::instruction_discriminator(&$crate::ident_name::<$ix_type>()) => {
+                      $crate::__discriminator_pattern!(@exact discrim$(, [$($discrim),*])?)
+                          if $crate::__discriminator_guard!(@exact instruction, $ix_type, discrim$(, [$($discrim),*])?) =>
+                      {
                           let name = $crate::ident_name::<$ix_type>();
                           Ok(name)
                       },
                     )*
-                    _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid instruction discriminator".to_string())))
+                    _ => Err($crate::DecodeError::UnknownDiscriminator(discrim.to_vec()))
                 }
             }
         }
@@ -179,57 +817,79 @@ macro_rules! derive_instruction_type {
 
 #[macro_export]
 macro_rules! derive_event_type {
-    ($vis:vis enum $ident:ident {
-        $($variant:ident ($event_type:ty)),*$(,)?
+    ($(#[$attr:meta])* $vis:vis enum $ident:ident {
+        $($variant:ident ($event_type:ty) $(= [$($discrim:literal),*$(,)?])?),*$(,)?
     }) => {
         #[derive(anchor_lang::prelude::AnchorDeserialize, anchor_lang::prelude::AnchorSerialize)]
+        $(#[$attr])*
         $vis enum $ident {
             $($variant($event_type),)*
         }
 
+        $($crate::__impl_discriminator!($event_type$(, [$($discrim),*])?);)*
+
         impl $crate::Decode for $ident {
-          fn decode(data: &[u8]) -> std::result::Result<Self, Box<dyn std::error::Error>> {
-            let discrim: &[u8; 8] = data[..8].try_into().map_err(|e| {
-              Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Event data is not 8 bytes or more".to_string()))
-            })?;
-            match discrim {
+          fn decode(data: &[u8]) -> std::result::Result<Self, $crate::DecodeError> {
+            match data {
               $(
-                $variant if discrim == &$crate::event_discriminator(&$crate::ident_name::<$event_type>()) => {
-                    // let event = <$event_type>::try_from_slice(&data[8..])?;
-                    // Ok(Self::$variant(event.clone()))
-                    let event = <$event_type>::deserialize(&mut &data[8..])?;
+                $crate::__discriminator_pattern!(rest$(, [$($discrim),*])?)
+                    if $crate::__discriminator_guard!(event, $event_type, rest$(, [$($discrim),*])?) =>
+                {
+                    let mut rest = $crate::__discriminator_rest!(event, $event_type, rest$(, [$($discrim),*])?);
+                    let event = <$event_type as anchor_lang::prelude::AnchorDeserialize>::deserialize(&mut rest)?;
                     Ok(Self::$variant(event))
                 },
               )*
-              _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid event discriminator".to_string())))
+              _ => Err($crate::__unmatched!(
+                  data,
+                  $($crate::__discriminator!(event, $event_type$(, [$($discrim),*])?)),*
+              )),
             }
           }
         }
 
+        impl $crate::Encode for $ident {
+            fn encode(&self) -> Vec<u8> {
+                match self {
+                    $(
+                      Self::$variant(value) => {
+                          let mut data = $crate::__discriminator!(event, $event_type$(, [$($discrim),*])?).to_vec();
+                          $crate::__encode_borsh!(value, &mut data);
+                          data
+                      },
+                    )*
+                }
+            }
+        }
+
+        impl $crate::DecodeCpiEvent for $ident {}
+
         impl $crate::NameToDiscrim for $ident {
-            fn name_to_discrim(name: &str) -> std::result::Result<[u8; 8], Box<dyn std::error::Error>> {
+            fn name_to_discrim(name: &str) -> std::result::Result<Vec<u8>, $crate::DecodeError> {
                 match name {
                     $(
-                      $variant if name == $crate::ident_name::<$event_type>() => {
-                          let discrim = $crate::event_discriminator(&$crate::ident_name::<$event_type>());
-                          Ok(discrim)
+                      _ if name == $crate::ident_name::<$event_type>() => {
+                          let discrim = $crate::__discriminator!(event, $event_type$(, [$($discrim),*])?);
+                          Ok(discrim.to_vec())
                       },
                     )*
-                    _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid event name".to_string())))
+                    _ => Err($crate::DecodeError::UnknownName(name.to_string()))
                 }
             }
         }
 
         impl $crate::DiscrimToName for $ident {
-            fn discrim_to_name(discrim: [u8; 8]) -> std::result::Result<String, Box<dyn std::error::Error>> {
+            fn discrim_to_name(discrim: &[u8]) -> std::result::Result<String, $crate::DecodeError> {
                 match discrim {
                     $(
-                      $variant if discrim == $crate::event_discriminator(&$crate::ident_name::<$event_type>()) => {
+                      $crate::__discriminator_pattern!(@exact discrim$(, [$($discrim),*])?)
+                          if $crate::__discriminator_guard!(@exact event, $event_type, discrim$(, [$($discrim),*])?) =>
+                      {
                           let name = $crate::ident_name::<$event_type>();
                           Ok(name)
                       },
                     )*
-                    _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid event discriminator".to_string())))
+                    _ => Err($crate::DecodeError::UnknownDiscriminator(discrim.to_vec()))
                 }
             }
         }
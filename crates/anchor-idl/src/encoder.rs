@@ -0,0 +1,535 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::instruction::{AccountMeta, Instruction},
+};
+use anchor_syn::idl::types::{
+    EnumFields, Idl, IdlAccountItem, IdlField, IdlInstruction, IdlType, IdlTypeDefinition,
+    IdlTypeDefinitionTy,
+};
+use heck::{ToLowerCamelCase, ToSnakeCase};
+use serde_json::Value;
+
+use crate::{substitute_typedef_generics, IdlAddresses, IdlDiscriminators, LoadedIdl};
+
+/// An error encountered while encoding an instruction from JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodeError {
+    /// Path of the offending argument or account, e.g. `args.params.maxTs` or `accounts.user`.
+    pub path: String,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl EncodeError {
+    fn at(path: &str, message: impl Into<String>) -> Self {
+        EncodeError {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid `{}`: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Builds instructions of a program whose IDL is only known at runtime.
+///
+/// Arguments are given as a JSON object keyed by the names of the arguments in the IDL, and are
+/// checked against their [IdlType]s before being serialized with Borsh:
+///
+/// - integers are JSON numbers, or strings for those which do not fit in a double;
+/// - public keys are base58 strings, and `bytes` are arrays of numbers;
+/// - `Option`s are `null` or their value, and `Vec`s and arrays are JSON arrays;
+/// - structs are objects with every field of the struct;
+/// - enum variants are their name, e.g. `"Long"`, or an object with a single key, the name of the
+///   variant, whose value is the object or array of its fields, e.g. `{ "limit": { "price": 5 } }`;
+/// - enum variants may also be tagged as the `serde` option renders them, e.g.
+///   `{ "kind": "Limit", "fields": { "price": 5 } }`, so that JSON of generated types is accepted.
+///
+/// Accounts are keyed by their name in the IDL; the accounts of a nested account struct are
+/// keyed by the name of the struct and that of the account, e.g. `"group.account"`. Missing
+/// accounts whose address is fixed by the IDL default to it, see [IdlEncoder::with_addresses],
+/// and missing optional accounts are passed as the program ID, as Anchor expects.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use anchor_idl::{parse_idl, IdlEncoder};
+/// use anchor_lang::prelude::Pubkey;
+///
+/// let loaded = parse_idl(
+///     r#"{
+///         "version": "0.1.0",
+///         "name": "counter",
+///         "instructions": [{
+///             "name": "increment",
+///             "accounts": [
+///                 { "name": "counter", "isMut": true, "isSigner": false },
+///                 { "name": "authority", "isMut": false, "isSigner": true }
+///             ],
+///             "args": [{ "name": "amount", "type": "u64" }]
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+/// let encoder = IdlEncoder::from(&loaded);
+///
+/// let program_id = Pubkey::new_unique();
+/// let accounts = BTreeMap::from([
+///     ("counter".to_string(), Pubkey::new_unique()),
+///     ("authority".to_string(), Pubkey::new_unique()),
+/// ]);
+/// let ix = encoder
+///     .encode_instruction(&program_id, "increment", &serde_json::json!({ "amount": 7 }), &accounts)
+///     .unwrap();
+/// assert_eq!(&ix.data[..8], &anchor_idl::instruction_discriminator("increment"));
+/// assert_eq!(&ix.data[8..], &7u64.to_le_bytes());
+/// assert!(ix.accounts[0].is_writable && ix.accounts[1].is_signer);
+///
+/// let err = encoder
+///     .encode_instruction(&program_id, "increment", &serde_json::json!({ "amount": -1 }), &accounts)
+///     .unwrap_err();
+/// assert_eq!(err.path, "args.amount");
+/// ```
+#[derive(Clone, Debug)]
+pub struct IdlEncoder<'a> {
+    idl: &'a Idl,
+    discriminators: &'a IdlDiscriminators,
+    addresses: IdlAddresses,
+}
+
+impl<'a> From<&'a LoadedIdl> for IdlEncoder<'a> {
+    fn from(loaded: &'a LoadedIdl) -> Self {
+        IdlEncoder::new(&loaded.idl, &loaded.discriminators).with_addresses(&loaded.addresses)
+    }
+}
+
+impl<'a> IdlEncoder<'a> {
+    /// Creates an encoder for `idl`. Discriminators missing from `discriminators` are derived from
+    /// the names of the instructions.
+    pub fn new(idl: &'a Idl, discriminators: &'a IdlDiscriminators) -> Self {
+        IdlEncoder {
+            idl,
+            discriminators,
+            addresses: IdlAddresses::default(),
+        }
+    }
+
+    /// Defaults the accounts whose address is fixed in `addresses`, as recorded by IDLs following
+    /// the Anchor 0.30 specification, to that address. Accounts which are given take precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use anchor_idl::{parse_idl, IdlEncoder};
+    /// use anchor_lang::{prelude::Pubkey, system_program};
+    ///
+    /// let loaded = parse_idl(
+    ///     r#"{
+    ///         "address": "Counter111111111111111111111111111111111111",
+    ///         "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+    ///         "instructions": [{
+    ///             "name": "create",
+    ///             "discriminator": [24, 30, 200, 40, 5, 28, 7, 119],
+    ///             "accounts": [
+    ///                 { "name": "payer", "writable": true, "signer": true },
+    ///                 { "name": "system_program", "address": "11111111111111111111111111111111" }
+    ///             ],
+    ///             "args": []
+    ///         }]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    /// // `IdlEncoder::from` passes the addresses of the IDL.
+    /// let encoder = IdlEncoder::new(&loaded.idl, &loaded.discriminators)
+    ///     .with_addresses(&loaded.addresses);
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let accounts = BTreeMap::from([("payer".to_string(), Pubkey::new_unique())]);
+    /// let ix = encoder
+    ///     .encode_instruction(&program_id, "create", &serde_json::json!({}), &accounts)
+    ///     .unwrap();
+    /// assert_eq!(ix.accounts[1].pubkey, system_program::ID);
+    /// ```
+    pub fn with_addresses(mut self, addresses: &IdlAddresses) -> Self {
+        self.addresses = addresses.clone();
+        self
+    }
+
+    /// Builds the instruction named `name`, in either camelCase or snake_case, from its arguments
+    /// and the public keys of its accounts.
+    pub fn encode_instruction(
+        &self,
+        program_id: &Pubkey,
+        name: &str,
+        args: &Value,
+        accounts: &BTreeMap<String, Pubkey>,
+    ) -> Result<Instruction, EncodeError> {
+        let ix = self.find_instruction(name)?;
+        let mut metas = vec![];
+        let fixed = self.addresses.instructions.get(&ix.name);
+        collect_account_metas(program_id, "", &ix.accounts, accounts, fixed, &mut metas)?;
+        if let Some(name) = accounts
+            .keys()
+            .find(|name| !has_account(name, "", &ix.accounts))
+        {
+            return Err(EncodeError::at(
+                &format!("accounts.{}", name),
+                format!("instruction {} has no such account", ix.name),
+            ));
+        }
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: metas,
+            data: self.encode_args(ix, args)?,
+        })
+    }
+
+    /// Serializes the arguments of `ix`, prefixed by its discriminator.
+    pub fn encode_args(&self, ix: &IdlInstruction, args: &Value) -> Result<Vec<u8>, EncodeError> {
+        let mut data = self.discriminators.instruction(&ix.name);
+        self.encode_fields(&ix.args, args, "args", &mut data)?;
+        Ok(data)
+    }
+
+    /// Serializes `value` as a value of type `ty`, appending it to `data`. `path` is that of the
+    /// value in errors.
+    pub fn encode_type(
+        &self,
+        ty: &IdlType,
+        value: &Value,
+        path: &str,
+        data: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match ty {
+            IdlType::Bool => {
+                let value = value
+                    .as_bool()
+                    .ok_or_else(|| mistyped(path, "a boolean", value))?;
+                data.push(value as u8);
+            }
+            IdlType::U8 => data.extend(parse_int::<u8>(value, path)?.to_le_bytes()),
+            IdlType::I8 => data.extend(parse_int::<i8>(value, path)?.to_le_bytes()),
+            IdlType::U16 => data.extend(parse_int::<u16>(value, path)?.to_le_bytes()),
+            IdlType::I16 => data.extend(parse_int::<i16>(value, path)?.to_le_bytes()),
+            IdlType::U32 => data.extend(parse_int::<u32>(value, path)?.to_le_bytes()),
+            IdlType::I32 => data.extend(parse_int::<i32>(value, path)?.to_le_bytes()),
+            IdlType::U64 => data.extend(parse_int::<u64>(value, path)?.to_le_bytes()),
+            IdlType::I64 => data.extend(parse_int::<i64>(value, path)?.to_le_bytes()),
+            IdlType::U128 => data.extend(parse_int::<u128>(value, path)?.to_le_bytes()),
+            IdlType::I128 => data.extend(parse_int::<i128>(value, path)?.to_le_bytes()),
+            IdlType::F32 => {
+                let value = value
+                    .as_f64()
+                    .ok_or_else(|| mistyped(path, "a number", value))?;
+                data.extend((value as f32).to_le_bytes());
+            }
+            IdlType::F64 => {
+                let value = value
+                    .as_f64()
+                    .ok_or_else(|| mistyped(path, "a number", value))?;
+                data.extend(value.to_le_bytes());
+            }
+            IdlType::U256 | IdlType::I256 => {
+                return Err(EncodeError::at(path, "256 bit integers are not supported"))
+            }
+            IdlType::String => {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| mistyped(path, "a string", value))?;
+                encode_len(value.len(), path, data)?;
+                data.extend(value.as_bytes());
+            }
+            IdlType::Bytes => {
+                let elements = value
+                    .as_array()
+                    .ok_or_else(|| mistyped(path, "an array of bytes", value))?;
+                encode_len(elements.len(), path, data)?;
+                for (i, element) in elements.iter().enumerate() {
+                    data.push(parse_int::<u8>(element, &format!("{}[{}]", path, i))?);
+                }
+            }
+            IdlType::PublicKey => {
+                let pubkey = value
+                    .as_str()
+                    .and_then(|value| Pubkey::from_str(value).ok())
+                    .ok_or_else(|| mistyped(path, "a base58 public key", value))?;
+                data.extend(pubkey.to_bytes());
+            }
+            IdlType::Option(inner) => match value {
+                Value::Null => data.push(0),
+                value => {
+                    data.push(1);
+                    self.encode_type(inner, value, path, data)?;
+                }
+            },
+            IdlType::Vec(inner) => {
+                let elements = value
+                    .as_array()
+                    .ok_or_else(|| mistyped(path, "an array", value))?;
+                encode_len(elements.len(), path, data)?;
+                self.encode_elements(inner, elements, path, data)?;
+            }
+            IdlType::Array(inner, len) => {
+                let elements = value
+                    .as_array()
+                    .filter(|elements| elements.len() == *len)
+                    .ok_or_else(|| {
+                        mistyped(path, &format!("an array of {} elements", len), value)
+                    })?;
+                self.encode_elements(inner, elements, path, data)?;
+            }
+            IdlType::Defined(name) => {
+                let def = self.find_typedef(name, path)?;
+                self.encode_typedef(&def.ty, value, path, data)?;
+            }
+            IdlType::DefinedWithTypeArgs { name, args } => {
+                let def = self.find_typedef(name, path)?;
+                self.encode_typedef(&substitute_typedef_generics(def, args), value, path, data)?;
+            }
+            IdlType::Generic(name) | IdlType::GenericLenArray(_, name) => {
+                return Err(EncodeError::at(
+                    path,
+                    format!("generic parameter {} is not bound", name),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_typedef(
+        &self,
+        ty: &IdlTypeDefinitionTy,
+        value: &Value,
+        path: &str,
+        data: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        match ty {
+            IdlTypeDefinitionTy::Struct { fields } => self.encode_fields(fields, value, path, data),
+            IdlTypeDefinitionTy::Enum { variants } => {
+                let (name, fields, tagged) = match value {
+                    Value::String(name) => (name.as_str(), &Value::Null, false),
+                    Value::Object(object) => match tagged_variant(object) {
+                        Some((name, fields)) => (name, fields, true),
+                        None if object.len() == 1 => {
+                            let (name, fields) = object.iter().next().unwrap();
+                            (name.as_str(), fields, false)
+                        }
+                        None => return Err(mistyped(path, "an enum variant", value)),
+                    },
+                    _ => return Err(mistyped(path, "an enum variant", value)),
+                };
+                let (index, variant) = variants
+                    .iter()
+                    .enumerate()
+                    .find(|(_, variant)| {
+                        variant.name.to_lower_camel_case() == name.to_lower_camel_case()
+                    })
+                    .ok_or_else(|| {
+                        EncodeError::at(path, format!("unknown enum variant {}", name))
+                    })?;
+                data.push(index as u8);
+                let path = format!("{}.{}", path, name);
+                match &variant.fields {
+                    None => match fields {
+                        Value::Null => Ok(()),
+                        Value::Object(object) if object.is_empty() => Ok(()),
+                        _ => Err(mistyped(&path, "no fields", fields)),
+                    },
+                    Some(EnumFields::Named(named)) => {
+                        self.encode_fields(named, fields, &path, data)
+                    }
+                    // serde renders the field of a newtype variant as is, not in an array.
+                    Some(EnumFields::Tuple(types)) if tagged && types.len() == 1 => {
+                        self.encode_type(&types[0], fields, &format!("{}[0]", path), data)
+                    }
+                    Some(EnumFields::Tuple(types)) => {
+                        let elements = fields
+                            .as_array()
+                            .filter(|elements| elements.len() == types.len())
+                            .ok_or_else(|| {
+                                mistyped(
+                                    &path,
+                                    &format!("an array of {} fields", types.len()),
+                                    fields,
+                                )
+                            })?;
+                        for (i, (ty, element)) in types.iter().zip(elements).enumerate() {
+                            self.encode_type(ty, element, &format!("{}[{}]", path, i), data)?;
+                        }
+                        Ok(())
+                    }
+                }
+            }
+            IdlTypeDefinitionTy::Alias { value: ty } => self.encode_type(ty, value, path, data),
+        }
+    }
+
+    fn encode_fields(
+        &self,
+        fields: &[IdlField],
+        value: &Value,
+        path: &str,
+        data: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| mistyped(path, "an object", value))?;
+        if let Some(name) = object
+            .keys()
+            .find(|name| !fields.iter().any(|field| &field.name == *name))
+        {
+            return Err(EncodeError::at(
+                &format!("{}.{}", path, name),
+                "unknown field",
+            ));
+        }
+        for field in fields {
+            let path = format!("{}.{}", path, field.name);
+            let value = object
+                .get(&field.name)
+                .ok_or_else(|| EncodeError::at(&path, "missing field"))?;
+            self.encode_type(&field.ty, value, &path, data)?;
+        }
+        Ok(())
+    }
+
+    fn encode_elements(
+        &self,
+        ty: &IdlType,
+        elements: &[Value],
+        path: &str,
+        data: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        for (i, element) in elements.iter().enumerate() {
+            self.encode_type(ty, element, &format!("{}[{}]", path, i), data)?;
+        }
+        Ok(())
+    }
+
+    fn find_instruction(&self, name: &str) -> Result<&'a IdlInstruction, EncodeError> {
+        self.idl
+            .instructions
+            .iter()
+            .find(|ix| ix.name == name || ix.name.to_snake_case() == name.to_snake_case())
+            .ok_or_else(|| EncodeError::at("instruction", format!("unknown instruction {}", name)))
+    }
+
+    fn find_typedef(&self, name: &str, path: &str) -> Result<&'a IdlTypeDefinition, EncodeError> {
+        self.idl
+            .types
+            .iter()
+            .chain(&self.idl.accounts)
+            .find(|def| def.name == name)
+            .ok_or_else(|| EncodeError::at(path, format!("type {} is not defined", name)))
+    }
+}
+
+/// Appends the metas of `items` to `metas` in the order of the IDL, flattening nested account
+/// structs. Accounts missing from `accounts` fall back to their address in `fixed`.
+fn collect_account_metas(
+    program_id: &Pubkey,
+    prefix: &str,
+    items: &[IdlAccountItem],
+    accounts: &BTreeMap<String, Pubkey>,
+    fixed: Option<&BTreeMap<String, Pubkey>>,
+    metas: &mut Vec<AccountMeta>,
+) -> Result<(), EncodeError> {
+    for item in items {
+        match item {
+            IdlAccountItem::IdlAccount(account) => {
+                let name = format!("{}{}", prefix, account.name);
+                let pubkey = accounts
+                    .get(&name)
+                    .or_else(|| fixed.and_then(|fixed| fixed.get(&name)));
+                let meta = match pubkey {
+                    Some(pubkey) if account.is_mut => AccountMeta::new(*pubkey, account.is_signer),
+                    Some(pubkey) => AccountMeta::new_readonly(*pubkey, account.is_signer),
+                    None if account.is_optional.unwrap_or(false) => {
+                        AccountMeta::new_readonly(*program_id, false)
+                    }
+                    None => {
+                        return Err(EncodeError::at(
+                            &format!("accounts.{}", name),
+                            "missing account",
+                        ))
+                    }
+                };
+                metas.push(meta);
+            }
+            IdlAccountItem::IdlAccounts(inner) => collect_account_metas(
+                program_id,
+                &format!("{}{}.", prefix, inner.name),
+                &inner.accounts,
+                accounts,
+                fixed,
+                metas,
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` is the key of one of the accounts in `items`.
+fn has_account(name: &str, prefix: &str, items: &[IdlAccountItem]) -> bool {
+    items.iter().any(|item| match item {
+        IdlAccountItem::IdlAccount(account) => name == format!("{}{}", prefix, account.name),
+        IdlAccountItem::IdlAccounts(inner) => {
+            has_account(name, &format!("{}{}.", prefix, inner.name), &inner.accounts)
+        }
+    })
+}
+
+/// The name and fields of an enum variant tagged as the `serde` option renders it, i.e. a `kind`
+/// string naming the variant and, if it has any, its `fields`.
+fn tagged_variant(object: &serde_json::Map<String, Value>) -> Option<(&str, &Value)> {
+    let name = object.get("kind")?.as_str()?;
+    if object.keys().any(|key| key != "kind" && key != "fields") {
+        return None;
+    }
+    Some((name, object.get("fields").unwrap_or(&Value::Null)))
+}
+
+/// Parses an integer given as a JSON number or a string.
+fn parse_int<T>(value: &Value, path: &str) -> Result<T, EncodeError>
+where
+    T: FromStr + TryFrom<i64> + TryFrom<u64>,
+{
+    let parsed = match value {
+        Value::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(value), _) => T::try_from(value).ok(),
+            (_, Some(value)) => T::try_from(value).ok(),
+            _ => None,
+        },
+        Value::String(value) => value.parse::<T>().ok(),
+        _ => None,
+    };
+    parsed.ok_or_else(|| {
+        mistyped(
+            path,
+            &format!("an integer of type {}", std::any::type_name::<T>()),
+            value,
+        )
+    })
+}
+
+fn encode_len(len: usize, path: &str, data: &mut Vec<u8>) -> Result<(), EncodeError> {
+    let len = u32::try_from(len).map_err(|_| EncodeError::at(path, "too many elements"))?;
+    data.extend(len.to_le_bytes());
+    Ok(())
+}
+
+fn mistyped(path: &str, expected: &str, value: &Value) -> EncodeError {
+    EncodeError::at(path, format!("expected {}, found {}", expected, value))
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+/// An error encountered while loading an IDL or generating code from it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratorError {
+    /// JSON path of the offending IDL node, e.g. `types[12].type.fields[3].type`.
+    pub path: Option<String>,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl GeneratorError {
+    /// Creates an error which does not relate to a specific IDL node.
+    pub fn new(message: impl Into<String>) -> Self {
+        GeneratorError {
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    /// Creates an error relating to the IDL node at `path`.
+    pub fn at(path: impl Into<String>, message: impl Into<String>) -> Self {
+        GeneratorError {
+            path: Some(path.into()),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "invalid IDL at `{}`: {}", path, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for GeneratorError {
+    fn from(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = err.path().to_string();
+        let message = err.into_inner().to_string();
+        if path == "." {
+            GeneratorError::new(message)
+        } else {
+            GeneratorError::at(path, message)
+        }
+    }
+}
+
+/// Result of loading an IDL or generating code from it.
+pub type GeneratorResult<T> = Result<T, GeneratorError>;
@@ -0,0 +1,170 @@
+use anchor_syn::idl::types::IdlErrorCode;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use serde_json::Value;
+
+/// Extracts `code` from a `TransactionError::InstructionError(_, InstructionError::Custom(code))`.
+///
+/// Takes any serializable error, as on chain crates cannot depend on the `solana-sdk` crate that
+/// defines `TransactionError`. The variants are matched by name, as serialized by `serde`.
+///
+/// ```
+/// # use anchor_idl::transaction_error_code;
+/// #[derive(serde::Serialize)]
+/// enum InstructionError {
+///     Custom(u32),
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// enum TransactionError {
+///     AccountInUse,
+///     InstructionError(u8, InstructionError),
+/// }
+///
+/// let err = TransactionError::InstructionError(0, InstructionError::Custom(6003));
+/// assert_eq!(transaction_error_code(&err), Some(6003));
+/// assert_eq!(transaction_error_code(&TransactionError::AccountInUse), None);
+/// ```
+pub fn transaction_error_code<E: serde::Serialize>(err: &E) -> Option<u32> {
+    match serde_json::to_value(err).ok()? {
+        Value::Object(err) => match err.get("InstructionError")? {
+            Value::Array(err) => err.get(1)?.get("Custom")?.as_u64()?.try_into().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Generates the `ErrorCode` enum of the errors a program may return.
+///
+/// Each variant's discriminant is its IDL error code, so the enum numbers errors exactly as
+/// Anchor's `#[error_code]` did in the original program.
+pub fn generate_error_code(errors: &[IdlErrorCode]) -> TokenStream {
+    let names = errors
+        .iter()
+        .map(|error| format_ident!("{}", error.name))
+        .collect::<Vec<_>>();
+    let codes = errors.iter().map(|error| error.code).collect::<Vec<_>>();
+    let name_strs = errors.iter().map(|error| &error.name).collect::<Vec<_>>();
+    let msgs = errors
+        .iter()
+        .map(|error| error.msg.as_ref().unwrap_or(&error.name))
+        .collect::<Vec<_>>();
+    let docs = msgs.iter().map(|msg| format!(" {}", msg));
+
+    quote! {
+        /// Errors returned by the program.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u32)]
+        pub enum ErrorCode {
+            #(
+                #[doc = #docs]
+                #names = #codes,
+            )*
+        }
+
+        impl ErrorCode {
+            /// Code of the error, as found in `ProgramError::Custom`.
+            pub fn code(&self) -> u32 {
+                *self as u32
+            }
+
+            /// Name of the error in the IDL.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#names => #name_strs,)*
+                }
+            }
+
+            /// Message of the error, falling back to its name if the IDL has no message.
+            pub fn msg(&self) -> &'static str {
+                match self {
+                    #(Self::#names => #msgs,)*
+                }
+            }
+
+            /// Maps a `ProgramError::Custom` returned by the program to its error.
+            pub fn from_program_error(err: &ProgramError) -> Option<Self> {
+                match err {
+                    ProgramError::Custom(code) => Self::try_from(*code).ok(),
+                    _ => None,
+                }
+            }
+
+            /// Maps an `InstructionError::Custom` returned by the program to its error.
+            ///
+            /// Failed transactions report these as `TransactionError::InstructionError`.
+            pub fn from_instruction_error<E>(err: E) -> Option<Self>
+            where
+                ProgramError: TryFrom<E>,
+            {
+                ProgramError::try_from(err)
+                    .ok()
+                    .and_then(|err| Self::from_program_error(&err))
+            }
+
+            /// Maps a `TransactionError::InstructionError` of a failed transaction to the error
+            /// of its `InstructionError::Custom`.
+            ///
+            /// Takes the `TransactionError` of `solana_sdk`, matched by its `serde` form.
+            pub fn from_transaction_error<E: anchor_gen::serde::Serialize>(err: &E) -> Option<Self> {
+                anchor_gen::transaction_error_code(err).and_then(|code| Self::try_from(code).ok())
+            }
+
+            /// Maps an Anchor error returned by the program to its error.
+            pub fn from_anchor_error(err: &anchor_lang::error::Error) -> Option<Self> {
+                match err {
+                    anchor_lang::error::Error::AnchorError(err) => {
+                        Self::try_from(err.error_code_number).ok()
+                    }
+                    anchor_lang::error::Error::ProgramError(err) => {
+                        Self::from_program_error(&err.program_error)
+                    }
+                }
+            }
+        }
+
+        impl TryFrom<u32> for ErrorCode {
+            type Error = u32;
+
+            fn try_from(code: u32) -> std::result::Result<Self, u32> {
+                match code {
+                    #(#codes => Ok(Self::#names),)*
+                    _ => Err(code),
+                }
+            }
+        }
+
+        impl From<ErrorCode> for u32 {
+            fn from(err: ErrorCode) -> u32 {
+                err.code()
+            }
+        }
+
+        impl From<ErrorCode> for ProgramError {
+            fn from(err: ErrorCode) -> ProgramError {
+                ProgramError::Custom(err.code())
+            }
+        }
+
+        impl From<ErrorCode> for anchor_lang::error::Error {
+            fn from(err: ErrorCode) -> anchor_lang::error::Error {
+                anchor_lang::error::Error::from(anchor_lang::error::AnchorError {
+                    error_name: err.name().to_string(),
+                    error_code_number: err.code(),
+                    error_msg: err.msg().to_string(),
+                    error_origin: None,
+                    compared_values: None,
+                })
+            }
+        }
+
+        impl std::fmt::Display for ErrorCode {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.msg())
+            }
+        }
+
+        impl std::error::Error for ErrorCode {}
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
 use anchor_syn::idl::types::*;
 use heck::{ToPascalCase, ToSnakeCase};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
+use crate::{AccountTypes, IdlAddresses, IdlDiscriminators};
+
 /// Generates a single instruction handler.
 pub fn generate_ix_handler(ix: &IdlInstruction) -> TokenStream {
     let ix_name = format_ident!("{}", ix.name.to_snake_case());
@@ -20,9 +25,11 @@ pub fn generate_ix_handler(ix: &IdlInstruction) -> TokenStream {
             }
         })
         .collect::<Vec<_>>();
+    let docs = crate::generate_docs(ix.docs.as_deref());
 
     if cfg!(feature = "compat-program-result") {
         quote! {
+            #docs
             pub fn #ix_name(
                 _ctx: Context<#accounts_name>,
                 #(#args),*
@@ -32,6 +39,7 @@ pub fn generate_ix_handler(ix: &IdlInstruction) -> TokenStream {
         }
     } else {
         quote! {
+            #docs
             pub fn #ix_name(
                 _ctx: Context<#accounts_name>,
                 #(#args),*
@@ -43,12 +51,15 @@ pub fn generate_ix_handler(ix: &IdlInstruction) -> TokenStream {
 }
 
 /// Generates instruction context structs.
-pub fn generate_ix_structs(ixs: &[IdlInstruction]) -> TokenStream {
+pub fn generate_ix_structs(ixs: &[IdlInstruction], account_types: &AccountTypes) -> TokenStream {
     let defs = ixs.iter().map(|ix| {
         let accounts_name = format_ident!("{}", ix.name.to_pascal_case());
 
-        let (all_structs, all_fields) =
-            crate::generate_account_fields(&ix.name.to_pascal_case(), &ix.accounts);
+        let (all_structs, all_fields) = crate::generate_account_fields(
+            &ix.name.to_pascal_case(),
+            &ix.accounts,
+            &account_types.for_instruction(&ix.accounts),
+        );
 
         quote! {
             #all_structs
@@ -64,6 +75,89 @@ pub fn generate_ix_structs(ixs: &[IdlInstruction]) -> TokenStream {
     }
 }
 
+/// Generates `Default` for the client accounts structs of the instructions which have accounts
+/// whose address is fixed by the IDL. The default fills in those addresses, leaving the other
+/// accounts as `Pubkey::default()` and optional accounts as `None`.
+pub fn generate_ix_account_defaults(
+    ixs: &[IdlInstruction],
+    addresses: &IdlAddresses,
+) -> TokenStream {
+    let defaults = ixs.iter().filter_map(|ix| {
+        let ix_addresses = addresses.instructions.get(&ix.name)?;
+        Some(generate_account_defaults(
+            &ix.name.to_pascal_case(),
+            &ix.accounts,
+            "",
+            ix_addresses,
+        ))
+    });
+    quote! {
+        #(#defaults)*
+    }
+}
+
+/// Generates `Default` for the client accounts struct `name` generated by `#[derive(Accounts)]`,
+/// and for those of its nested account structs, named as [crate::generate_account_fields] does.
+fn generate_account_defaults(
+    name: &str,
+    accounts: &[IdlAccountItem],
+    prefix: &str,
+    addresses: &BTreeMap<String, Pubkey>,
+) -> TokenStream {
+    let mut sub_defaults = vec![];
+    let fields = accounts
+        .iter()
+        .map(|account| match account {
+            IdlAccountItem::IdlAccount(info) => {
+                let acc_name = format_ident!("{}", info.name.to_snake_case());
+                let address = addresses
+                    .get(&format!("{}{}", prefix, info.name))
+                    .map(|address| {
+                        let address = address.to_string();
+                        quote! { std::str::FromStr::from_str(#address).unwrap() }
+                    });
+                let value = match (address, info.is_optional.unwrap_or(false)) {
+                    (Some(address), true) => quote! { Some(#address) },
+                    (Some(address), false) => address,
+                    (None, true) => quote! { None },
+                    (None, false) => quote! { Pubkey::default() },
+                };
+                quote! {
+                    #acc_name: #value
+                }
+            }
+            IdlAccountItem::IdlAccounts(inner) => {
+                let field_name = format_ident!("{}{}", name, inner.name.to_snake_case());
+                let sub_name = format!("{}{}", name, inner.name.to_pascal_case());
+                sub_defaults.push(generate_account_defaults(
+                    &sub_name,
+                    &inner.accounts,
+                    &format!("{}{}.", prefix, inner.name),
+                    addresses,
+                ));
+                quote! {
+                    #field_name: Default::default()
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // The client struct is defined in a module named after the struct by `#[derive(Accounts)]`.
+    let client_mod = format_ident!("__client_accounts_{}", name.to_snake_case());
+    let client_struct = format_ident!("{}", name);
+    quote! {
+        #(#sub_defaults)*
+
+        impl Default for #client_mod::#client_struct {
+            fn default() -> Self {
+                Self {
+                    #(#fields),*
+                }
+            }
+        }
+    }
+}
+
 /// Generates all instruction handlers.
 pub fn generate_ix_handlers(ixs: &[IdlInstruction]) -> TokenStream {
     let streams = ixs.iter().map(generate_ix_handler);
@@ -71,3 +165,153 @@ pub fn generate_ix_handlers(ixs: &[IdlInstruction]) -> TokenStream {
         #(#streams)*
     }
 }
+
+/// Generates a function which builds an [Instruction](anchor_lang::solana_program::instruction::Instruction)
+/// for off-chain clients.
+///
+/// The instruction data is the discriminator followed by the Borsh-serialized arguments.
+/// Optional accounts which are omitted are passed as the program ID, as Anchor expects. If the IDL
+/// fixes the address of some accounts, the docs of the builder point to the `Default` of its
+/// accounts struct, which fills them in.
+///
+/// The accounts are taken as `__accounts`, so that they do not collide with an argument named
+/// `accounts` in the IDL.
+pub fn generate_ix_builder(
+    ix: &IdlInstruction,
+    discriminator: &[u8],
+    addresses: &IdlAddresses,
+) -> TokenStream {
+    let ix_name = format_ident!("{}", ix.name.to_snake_case());
+    let ix_struct = format_ident!("{}", ix.name.to_pascal_case());
+
+    let arg_names = ix
+        .args
+        .iter()
+        .map(|arg| format_ident!("{}", arg.name.to_snake_case()))
+        .collect::<Vec<_>>();
+    let param_names = ix
+        .args
+        .iter()
+        .map(|arg| format_ident!("_{}", arg.name.to_snake_case()));
+    let arg_types = ix.args.iter().map(|arg| {
+        let type_name = crate::ty_to_rust_type(&arg.ty);
+        let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
+        stream
+    });
+
+    let docs = crate::generate_docs(ix.docs.as_deref());
+    let summary = format!(" Builds a `{}` instruction.", ix.name.to_snake_case());
+    let separator = ix.docs.as_ref().map(|_| quote! { #[doc = ""] });
+    let defaults = addresses.instructions.contains_key(&ix.name).then(|| {
+        let defaults = format!(
+            " Accounts whose address is fixed by the IDL default to it, e.g. \
+            `accounts::{} {{ ..., ..Default::default() }}`.",
+            ix_struct
+        );
+        quote! {
+            #[doc = ""]
+            #[doc = #defaults]
+        }
+    });
+
+    quote! {
+        #[doc = #summary]
+        #separator
+        #docs
+        #defaults
+        pub fn #ix_name(
+            __accounts: accounts::#ix_struct,
+            #(#arg_names: #arg_types),*
+        ) -> anchor_lang::solana_program::instruction::Instruction {
+            // Arguments are moved before any local can shadow them.
+            let args = instruction::#ix_struct {
+                #(#param_names: #arg_names),*
+            };
+            let mut data = vec![#(#discriminator),*];
+            AnchorSerialize::serialize(&args, &mut data).unwrap();
+            anchor_lang::solana_program::instruction::Instruction {
+                program_id: ID,
+                // Anchor passes the program ID in place of optional accounts which are omitted.
+                accounts: __accounts.to_account_metas(None),
+                data,
+            }
+        }
+    }
+}
+
+/// Generates instruction builders for all instructions.
+pub fn generate_ix_builders(
+    ixs: &[IdlInstruction],
+    discriminators: &IdlDiscriminators,
+    addresses: &IdlAddresses,
+) -> TokenStream {
+    let streams = ixs
+        .iter()
+        .map(|ix| generate_ix_builder(ix, &discriminators.instruction(&ix.name), addresses));
+    quote! {
+        #(#streams)*
+    }
+}
+
+/// Generates the serde implementations of the arguments of an instruction, which are defined by
+/// the `#[program]` macro and hence cannot derive them. Arguments are keyed by their name in the
+/// IDL.
+pub fn generate_ix_serde(ix: &IdlInstruction) -> TokenStream {
+    let ix_struct = format_ident!("{}", ix.name.to_pascal_case());
+    let remote_struct = format_ident!("{}Serde", ix.name.to_pascal_case());
+    let remote = format!("instruction::{}", ix_struct);
+    let derive_serde = crate::generate_serde_derive(&Default::default(), true);
+
+    let remote_def = if ix.args.is_empty() {
+        quote! {
+            struct #remote_struct;
+        }
+    } else {
+        let fields = ix.args.iter().map(|arg| {
+            let name = format_ident!("_{}", arg.name.to_snake_case());
+            let type_name = crate::ty_to_rust_type(&arg.ty);
+            let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
+            let serde_attrs = crate::generate_serde_field_attrs(&name, &arg.name, &arg.ty, true);
+            quote! {
+                #serde_attrs
+                #name: #stream
+            }
+        });
+        quote! {
+            struct #remote_struct {
+                #(#fields),*
+            }
+        }
+    };
+
+    quote! {
+        #derive_serde
+        #[serde(remote = #remote)]
+        #remote_def
+
+        impl anchor_gen::serde::Serialize for instruction::#ix_struct {
+            fn serialize<S: anchor_gen::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                #remote_struct::serialize(self, serializer)
+            }
+        }
+
+        impl<'de> anchor_gen::serde::Deserialize<'de> for instruction::#ix_struct {
+            fn deserialize<D: anchor_gen::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                #remote_struct::deserialize(deserializer)
+            }
+        }
+    }
+}
+
+/// Generates the serde implementations of the arguments of all instructions.
+pub fn generate_ix_serdes(ixs: &[IdlInstruction]) -> TokenStream {
+    let streams = ixs.iter().map(generate_ix_serde);
+    quote! {
+        #(#streams)*
+    }
+}
@@ -1,20 +1,45 @@
 //! Generates Rust code from an Anchor IDL.
 
 pub use anchor_syn::idl::*;
+use proc_macro2::TokenStream;
+use quote::quote;
 
 mod account;
+mod builder;
+mod constant;
 mod decode;
+mod encoder;
+mod error;
+mod error_code;
 mod instruction;
+mod logs;
+mod pda;
 mod program;
+pub mod serde_as;
+mod spec;
 mod state;
 mod typedef;
+mod validate;
+mod value;
 
 pub use account::*;
+pub use builder::*;
+pub use constant::*;
 pub use decode::*;
+pub use encoder::*;
+pub use error::*;
+pub use error_code::*;
 pub use instruction::*;
+pub use logs::*;
+pub use pda::*;
 pub use program::*;
+pub use spec::*;
 pub use state::*;
 pub use typedef::*;
+pub use validate::*;
+pub use value::*;
+
+pub use serde;
 
 /// Version of anchor-idl.
 pub const GEN_VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
@@ -44,8 +69,29 @@ pub fn ty_to_rust_type(ty: &IdlType) -> String {
         IdlType::Vec(inner) => format!("Vec<{}>", ty_to_rust_type(inner)),
         IdlType::Array(ty, size) => format!("[{}; {}]", ty_to_rust_type(ty), size),
         IdlType::Defined(name) => name.to_string(),
-        IdlType::GenericLenArray(..) => todo!(),
-        IdlType::Generic(..) => todo!(),
-        IdlType::DefinedWithTypeArgs { .. } => todo!(),
+        IdlType::GenericLenArray(ty, len) => format!("[{}; {}]", ty_to_rust_type(ty), len),
+        IdlType::Generic(name) => name.to_string(),
+        IdlType::DefinedWithTypeArgs { name, args } => {
+            let args = args
+                .iter()
+                .map(|arg| match arg {
+                    IdlDefinedTypeArg::Generic(name) => name.to_string(),
+                    IdlDefinedTypeArg::Value(value) => value.to_string(),
+                    IdlDefinedTypeArg::Type(ty) => ty_to_rust_type(ty),
+                })
+                .collect::<Vec<_>>();
+            format!("{}<{}>", name, args.join(", "))
+        }
+    }
+}
+
+/// Generates `#[doc]` attributes from the `docs` of an IDL node, one per line.
+pub fn generate_docs(docs: Option<&[String]>) -> TokenStream {
+    let lines = docs
+        .unwrap_or_default()
+        .iter()
+        .map(|line| format!(" {}", line));
+    quote! {
+        #(#[doc = #lines])*
     }
 }
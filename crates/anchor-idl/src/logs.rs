@@ -0,0 +1,130 @@
+use std::{fmt::Display, marker::PhantomData, slice};
+
+use crate::{Decode, DecodeError};
+
+/// An event decoded from the log messages of a transaction.
+#[derive(Clone, Debug)]
+pub struct LogEvent<E> {
+    /// The decoded event.
+    pub event: E,
+    /// Depth of the invocation of the program which emitted the event: 1 if it was invoked by the
+    /// transaction, 2 if it was invoked through a CPI, and so on.
+    pub depth: usize,
+}
+
+/// Iterator over the events emitted by a program in the log messages of a transaction.
+///
+/// Events are the `Program data: <base64>` lines logged by Anchor's `emit!`. The invocation stack
+/// is tracked through the `Program <id> invoke [n]` and `Program <id> success` / `failed` lines,
+/// so that only the events logged while the program itself is executing are decoded, including
+/// when it is invoked through a CPI. Data which cannot be decoded is returned as an error, and
+/// iteration continues with the next line.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{derive_event_type, Encode, LogEvents};
+/// use anchor_lang::prelude::*;
+///
+/// declare_id!("GjphYQcbP1m3FuDyCTUJf2mUMxKPE3j6feWU1rxvC7Ps");
+///
+/// #[event]
+/// pub struct Incremented {
+///     pub count: u64,
+/// }
+///
+/// derive_event_type!(
+///     pub enum EventType {
+///         Incremented(Incremented),
+///     }
+/// );
+///
+/// fn main() {
+///     let data = base64::encode(EventType::Incremented(Incremented { count: 7 }).encode());
+///     let other = Pubkey::new_unique();
+///     let logs = vec![
+///         format!("Program {} invoke [1]", other),
+///         format!("Program {} invoke [2]", ID),
+///         format!("Program data: {}", data),
+///         format!("Program {} success", ID),
+///         // Logged by the caller, so it is not decoded.
+///         format!("Program data: {}", data),
+///         format!("Program {} success", other),
+///     ];
+///
+///     let events = LogEvents::<_, EventType>::new(&ID, &logs)
+///         .collect::<std::result::Result<Vec<_>, _>>()
+///         .unwrap();
+///     assert_eq!(events.len(), 1);
+///     assert_eq!(events[0].depth, 2);
+///     match &events[0].event {
+///         EventType::Incremented(event) => assert_eq!(event.count, 7),
+///     }
+/// }
+/// ```
+pub struct LogEvents<'a, L, E> {
+    program_id: String,
+    logs: slice::Iter<'a, L>,
+    /// Programs currently executing, innermost last.
+    stack: Vec<&'a str>,
+    event: PhantomData<E>,
+}
+
+impl<'a, L: AsRef<str>, E: Decode> LogEvents<'a, L, E> {
+    /// Iterates over the events emitted by `program_id` in `logs`.
+    ///
+    /// The program ID is taken as anything which displays as base58, so that the `Pubkey` of any
+    /// version of Solana can be used.
+    pub fn new(program_id: &impl Display, logs: &'a [L]) -> Self {
+        LogEvents {
+            program_id: program_id.to_string(),
+            logs: logs.iter(),
+            stack: vec![],
+            event: PhantomData,
+        }
+    }
+}
+
+impl<'a, L: AsRef<str>, E: Decode> Iterator for LogEvents<'a, L, E> {
+    type Item = Result<LogEvent<E>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in self.logs.by_ref() {
+            let line = line.as_ref();
+            if let Some(data) = line.strip_prefix("Program data: ") {
+                if self.stack.last() != Some(&self.program_id.as_str()) {
+                    continue;
+                }
+                let depth = self.stack.len();
+                return Some(
+                    decode_log_data(data)
+                        .and_then(|data| E::decode(&data).map(|event| LogEvent { event, depth })),
+                );
+            }
+            let Some(rest) = line.strip_prefix("Program ") else {
+                continue;
+            };
+            let Some((program_id, status)) = rest.split_once(' ') else {
+                continue;
+            };
+            if status.starts_with("invoke [") {
+                self.stack.push(program_id);
+            } else if (status == "success" || status.starts_with("failed"))
+                && self.stack.last() == Some(&program_id)
+            {
+                self.stack.pop();
+            }
+        }
+        None
+    }
+}
+
+/// Decodes the base64 fields of a `Program data:` line, which are separated by spaces, into their
+/// concatenated bytes.
+fn decode_log_data(data: &str) -> Result<Vec<u8>, DecodeError> {
+    data.split_whitespace()
+        .try_fold(vec![], |mut bytes, field| {
+            bytes.extend(base64::decode(field).map_err(DecodeError::Base64)?);
+            Ok(bytes)
+        })
+}
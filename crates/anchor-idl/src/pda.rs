@@ -0,0 +1,212 @@
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_syn::idl::types::{
+    IdlAccountItem, IdlInstruction, IdlPda, IdlSeed, IdlSeedAccount, IdlSeedArg, IdlType,
+};
+use heck::ToSnakeCase;
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+
+/// A parameter of a PDA helper.
+struct SeedParam {
+    name: Ident,
+    ty: TokenStream,
+}
+
+/// Generates `find_<account>_address` functions for every account of an instruction whose seeds
+/// are described in the IDL.
+///
+/// Accounts with any seed which cannot be expressed as a parameter, e.g. a seed of a user-defined
+/// type, are skipped as a whole, as leaving the seed out would derive a different address.
+pub fn generate_ix_pdas(ix: &IdlInstruction) -> TokenStream {
+    let helpers = collect_pdas("", &ix.accounts)
+        .into_iter()
+        .filter_map(|(name, pda)| generate_pda(&name, pda));
+    quote! {
+        #(#helpers)*
+    }
+}
+
+/// Generates a module of PDA helpers for each instruction which has accounts with seeds.
+pub fn generate_pdas(ixs: &[IdlInstruction]) -> TokenStream {
+    let modules = ixs.iter().filter_map(|ix| {
+        let helpers = generate_ix_pdas(ix);
+        if helpers.is_empty() {
+            return None;
+        }
+        let mod_name = format_ident!("{}", ix.name.to_snake_case());
+        let doc = format!(
+            " Program derived addresses of accounts of the `{}` instruction.",
+            ix.name.to_snake_case()
+        );
+        Some(quote! {
+            #[doc = #doc]
+            pub mod #mod_name {
+                use super::*;
+                #helpers
+            }
+        })
+    });
+    quote! {
+        #(#modules)*
+    }
+}
+
+/// Returns the accounts of an instruction which have seeds, named after their path in nested
+/// account structs.
+fn collect_pdas<'a>(prefix: &str, accounts: &'a [IdlAccountItem]) -> Vec<(String, &'a IdlPda)> {
+    accounts
+        .iter()
+        .flat_map(|item| match item {
+            IdlAccountItem::IdlAccount(account) => account
+                .pda
+                .as_ref()
+                .map(|pda| (format!("{}{}", prefix, account.name.to_snake_case()), pda))
+                .into_iter()
+                .collect::<Vec<_>>(),
+            IdlAccountItem::IdlAccounts(inner) => collect_pdas(
+                &format!("{}{}_", prefix, inner.name.to_snake_case()),
+                &inner.accounts,
+            ),
+        })
+        .collect()
+}
+
+fn generate_pda(name: &str, pda: &IdlPda) -> Option<TokenStream> {
+    let mut params: Vec<SeedParam> = vec![];
+    let seeds = pda
+        .seeds
+        .iter()
+        .map(|seed| generate_seed(seed, &mut params))
+        .collect::<Option<Vec<_>>>()?;
+    let seed_params = params
+        .iter()
+        .map(|param| {
+            let SeedParam { name, ty } = param;
+            quote! { #name: #ty }
+        })
+        .collect::<Vec<_>>();
+
+    // A program ID which refers to an argument or account becomes the last parameter.
+    let program_id = match &pda.program_id {
+        None => quote! { &ID },
+        Some(IdlSeed::Const(seed)) => {
+            let bytes = const_seed_bytes(&seed.ty, &seed.value)?;
+            let bytes = Pubkey::try_from(bytes.as_slice()).ok()?.to_bytes();
+            quote! { &Pubkey::new_from_array([#(#bytes),*]) }
+        }
+        Some(
+            seed @ (IdlSeed::Arg(IdlSeedArg { path, .. })
+            | IdlSeed::Account(IdlSeedAccount { path, .. })),
+        ) => {
+            generate_seed(seed, &mut params)?;
+            let name = seed_param_name(path);
+            quote! { #name }
+        }
+    };
+    let params_rendered = params
+        .iter()
+        .map(|param| {
+            let SeedParam { name, ty } = param;
+            quote! { #name: #ty }
+        })
+        .collect::<Vec<_>>();
+    let seed_names = params.iter().map(|param| &param.name).collect::<Vec<_>>();
+    let seed_names = &seed_names[..seed_params.len()];
+
+    let find_fn = format_ident!("find_{}_address", name);
+    let find_with_program_id_fn = format_ident!("find_{}_address_with_program_id", name);
+    let doc = format!(" Finds the address of the `{}` account.", name);
+    let doc_with_program_id = format!(
+        " Finds the address of the `{}` account as derived by `program_id`.",
+        name
+    );
+
+    Some(quote! {
+        #[doc = #doc]
+        pub fn #find_fn(#(#params_rendered),*) -> (Pubkey, u8) {
+            #find_with_program_id_fn(#(#seed_names,)* #program_id)
+        }
+
+        #[doc = #doc_with_program_id]
+        pub fn #find_with_program_id_fn(
+            #(#seed_params,)*
+            program_id: &Pubkey
+        ) -> (Pubkey, u8) {
+            Pubkey::find_program_address(&[#(#seeds),*], program_id)
+        }
+    })
+}
+
+/// Renders a seed as a `&[u8]` expression, adding any parameter it references to `params`.
+fn generate_seed(seed: &IdlSeed, params: &mut Vec<SeedParam>) -> Option<TokenStream> {
+    let (ty, path) = match seed {
+        IdlSeed::Const(seed) => {
+            let bytes = Literal::byte_string(&const_seed_bytes(&seed.ty, &seed.value)?);
+            return Some(quote! { &#bytes[..] });
+        }
+        IdlSeed::Arg(seed) => (&seed.ty, &seed.path),
+        IdlSeed::Account(seed) => (&seed.ty, &seed.path),
+    };
+    let name = seed_param_name(path);
+    let (param_ty, bytes) = match ty {
+        IdlType::PublicKey => (quote! { &Pubkey }, quote! { #name.as_ref() }),
+        IdlType::String => (quote! { &str }, quote! { #name.as_bytes() }),
+        IdlType::Bytes => (quote! { &[u8] }, quote! { #name }),
+        IdlType::Array(inner, len) if **inner == IdlType::U8 => {
+            (quote! { &[u8; #len] }, quote! { #name.as_ref() })
+        }
+        IdlType::U8
+        | IdlType::I8
+        | IdlType::U16
+        | IdlType::I16
+        | IdlType::U32
+        | IdlType::I32
+        | IdlType::U64
+        | IdlType::I64
+        | IdlType::U128
+        | IdlType::I128 => {
+            let ty: TokenStream = crate::ty_to_rust_type(ty).parse().unwrap();
+            (ty, quote! { #name.to_le_bytes().as_ref() })
+        }
+        _ => return None,
+    };
+    if !params.iter().any(|param| param.name == name) {
+        params.push(SeedParam { name, ty: param_ty });
+    }
+    Some(bytes)
+}
+
+/// Name of the parameter of a seed which refers to an argument or account, e.g. `governor` or
+/// `governor_proposal_count` for `governor.proposal_count`.
+fn seed_param_name(path: &str) -> Ident {
+    format_ident!("{}", path.replace('.', "_").to_snake_case())
+}
+
+/// Converts the value of a constant seed to its bytes.
+fn const_seed_bytes(ty: &IdlType, value: &serde_json::Value) -> Option<Vec<u8>> {
+    match (ty, value) {
+        (IdlType::String, serde_json::Value::String(value)) => Some(value.as_bytes().to_vec()),
+        (IdlType::PublicKey, serde_json::Value::String(value)) => {
+            Some(Pubkey::from_str(value).ok()?.to_bytes().to_vec())
+        }
+        (_, serde_json::Value::Array(values)) => values
+            .iter()
+            .map(|value| u8::try_from(value.as_u64()?).ok())
+            .collect(),
+        (IdlType::U8, serde_json::Value::Number(value)) => {
+            Some(u8::try_from(value.as_u64()?).ok()?.to_le_bytes().to_vec())
+        }
+        (IdlType::U16, serde_json::Value::Number(value)) => {
+            Some(u16::try_from(value.as_u64()?).ok()?.to_le_bytes().to_vec())
+        }
+        (IdlType::U32, serde_json::Value::Number(value)) => {
+            Some(u32::try_from(value.as_u64()?).ok()?.to_le_bytes().to_vec())
+        }
+        (IdlType::U64, serde_json::Value::Number(value)) => {
+            Some(value.as_u64()?.to_le_bytes().to_vec())
+        }
+        _ => None,
+    }
+}
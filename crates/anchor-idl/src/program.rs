@@ -1,17 +1,28 @@
 use std::{
     collections::{BTreeMap, HashSet},
     env, fs,
-    path::PathBuf,
+    path::Path,
 };
 
-use darling::{util::PathList, FromMeta};
+use darling::{
+    util::{Flag, PathList},
+    FromMeta,
+};
 use heck::ToPascalCase;
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    LitStr, NestedMeta, Token,
+};
 
 use crate::{
-    generate_accounts, generate_events, generate_ix_handlers, generate_ix_structs,
-    generate_typedefs, GEN_VERSION,
+    generate_accounts, generate_constants, generate_error_code, generate_events,
+    generate_ix_account_defaults, generate_ix_builders, generate_ix_handlers, generate_ix_serdes,
+    generate_ix_structs, generate_pdas, generate_typedefs, parse_idl, AccountTypes, GeneratorError,
+    GeneratorResult, IdlAddresses, IdlDiscriminators, LoadedIdl, GEN_VERSION,
 };
 
 #[derive(Default, FromMeta)]
@@ -22,44 +33,125 @@ pub struct GeneratorOptions {
     pub zero_copy: Option<PathList>,
     /// List of `repr(packed)` structs.
     pub packed: Option<PathList>,
+    /// Render all non-signer instruction accounts as `AccountInfo`, rather than typing those
+    /// which hold a state account.
+    pub untyped_accounts: Flag,
+    /// Derive `Serialize` and `Deserialize` on generated types, through the serde re-exported by
+    /// anchor-gen. See [crate::serde_as] for how fields are represented.
+    pub serde: Flag,
 }
 
-fn path_list_to_string(list: Option<&PathList>) -> HashSet<String> {
+fn path_list_to_string(list: Option<&PathList>) -> GeneratorResult<HashSet<String>> {
     list.map(|el| {
         el.iter()
-            .map(|el| el.get_ident().unwrap().to_string())
+            .map(|el| {
+                el.get_ident()
+                    .map(|ident| ident.to_string())
+                    .ok_or_else(|| {
+                        GeneratorError::new(format!(
+                            "expected the name of a struct, found `{}`",
+                            quote!(#el)
+                        ))
+                    })
+            })
             .collect()
     })
-    .unwrap_or_default()
+    .unwrap_or_else(|| Ok(HashSet::new()))
 }
 
 impl GeneratorOptions {
-    pub fn to_generator(&self) -> Generator {
-        let cargo_manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-        let path = PathBuf::from(cargo_manifest_dir).join(&self.idl_path);
-        let idl_contents = fs::read_to_string(path).unwrap();
-        let idl: anchor_syn::idl::types::Idl = serde_json::from_str(&idl_contents).unwrap();
+    /// Creates a generator for the IDL, whose path is relative to the manifest of the crate being
+    /// compiled.
+    pub fn to_generator(&self) -> GeneratorResult<Generator> {
+        let cargo_manifest_dir = env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| GeneratorError::new("CARGO_MANIFEST_DIR is not set"))?;
+        self.to_generator_in(Path::new(&cargo_manifest_dir))
+    }
 
-        let zero_copy = path_list_to_string(self.zero_copy.as_ref());
-        let packed = path_list_to_string(self.packed.as_ref());
+    /// Creates a generator for the IDL, whose path is relative to `dir`.
+    pub fn to_generator_in(&self, dir: &Path) -> GeneratorResult<Generator> {
+        let path = dir.join(&self.idl_path);
+        let idl_contents = fs::read_to_string(&path).map_err(|e| {
+            GeneratorError::new(format!("could not read IDL {}: {}", path.display(), e))
+        })?;
+        let LoadedIdl {
+            idl,
+            struct_opts: mut idl_struct_opts,
+            discriminators,
+            addresses,
+            ..
+        } = parse_idl(&idl_contents)?;
+
+        let zero_copy = path_list_to_string(self.zero_copy.as_ref())?;
+        let packed = path_list_to_string(self.packed.as_ref())?;
 
         let mut struct_opts: BTreeMap<String, StructOpts> = BTreeMap::new();
         let all_structs: HashSet<&String> = zero_copy.union(&packed).collect::<HashSet<_>>();
         all_structs.into_iter().for_each(|name| {
+            let idl_opts = idl_struct_opts.remove(name).unwrap_or_default();
             struct_opts.insert(
                 name.to_string(),
                 StructOpts {
-                    zero_copy: zero_copy.contains(name),
-                    packed: packed.contains(name),
+                    zero_copy: idl_opts.zero_copy || zero_copy.contains(name),
+                    packed: idl_opts.packed || packed.contains(name),
                 },
             );
         });
+        struct_opts.extend(idl_struct_opts);
+
+        // Zero copy structs are cast from account data, which requires a single concrete layout.
+        if let Some(def) = idl.types.iter().chain(idl.accounts.iter()).find(|def| {
+            def.generics.as_ref().is_some_and(|g| !g.is_empty())
+                && struct_opts
+                    .get(&def.name)
+                    .is_some_and(|opts| opts.zero_copy)
+        }) {
+            return Err(GeneratorError::new(format!(
+                "zero copy struct {} cannot be generic",
+                def.name
+            )));
+        }
+
+        Ok(Generator {
+            idl,
+            struct_opts,
+            discriminators,
+            addresses,
+            untyped_accounts: self.untyped_accounts.is_present(),
+            serde: self.serde.is_present(),
+        })
+    }
+}
+
+/// Input of `generate_cpi_crate!`: the IDL path, optionally followed by generator options.
+pub struct CpiCrateInput {
+    pub idl_path: LitStr,
+    pub options: Punctuated<NestedMeta, Token![,]>,
+}
+
+impl Parse for CpiCrateInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let idl_path = input.parse()?;
+        let options = if input.parse::<Option<Token![,]>>()?.is_some() {
+            Punctuated::parse_terminated(input)?
+        } else {
+            Punctuated::new()
+        };
+        Ok(CpiCrateInput { idl_path, options })
+    }
+}
 
-        Generator { idl, struct_opts }
+impl CpiCrateInput {
+    /// The generator options of the input, as parsed by `generate_cpi_crate!`.
+    pub fn to_options(&self) -> darling::Result<GeneratorOptions> {
+        let idl_path = &self.idl_path;
+        let mut args: Vec<NestedMeta> = vec![parse_quote!(idl_path = #idl_path)];
+        args.extend(self.options.iter().cloned());
+        GeneratorOptions::from_list(&args)
     }
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct StructOpts {
     pub packed: bool,
     pub zero_copy: bool,
@@ -68,17 +160,25 @@ pub struct StructOpts {
 pub struct Generator {
     pub idl: anchor_syn::idl::types::Idl,
     pub struct_opts: BTreeMap<String, StructOpts>,
+    pub discriminators: IdlDiscriminators,
+    pub addresses: IdlAddresses,
+    pub untyped_accounts: bool,
+    pub serde: bool,
 }
 
 impl Generator {
-    pub fn __generate_cpi_interface(&self) -> TokenStream {
+    pub fn generate_cpi_interface(&self) -> GeneratorResult<TokenStream> {
         let idl = &self.idl;
         let program_name: Ident = format_ident!("{}", idl.name);
 
-        let accounts = generate_accounts(&idl.types, &idl.accounts, &self.struct_opts);
-        let typedefs = generate_typedefs(&idl.types, &self.struct_opts);
+        let accounts = generate_accounts(&idl.types, &idl.accounts, &self.struct_opts, self.serde)?;
+        let typedefs = generate_typedefs(&idl.types, &idl.accounts, &self.struct_opts, self.serde)?;
         let ix_handlers = generate_ix_handlers(&idl.instructions);
-        let ix_structs = generate_ix_structs(&idl.instructions);
+        let ix_structs = generate_ix_structs(&idl.instructions, &self.ix_account_types());
+        let ix_account_defaults = generate_ix_account_defaults(&idl.instructions, &self.addresses);
+        let ix_builders =
+            generate_ix_builders(&idl.instructions, &self.discriminators, &self.addresses);
+        let pdas = generate_pdas(&idl.instructions);
 
         let docs = format!(
             " Anchor CPI crate generated from {} v{} using [anchor-gen](https://crates.io/crates/anchor-gen) v{}.",
@@ -87,7 +187,80 @@ impl Generator {
             &GEN_VERSION.unwrap_or("unknown")
         );
 
-        quote! {
+        // Docs of the IDL follow the line above as a separate paragraph.
+        let program_docs = idl
+            .docs
+            .iter()
+            .flatten()
+            .map(|line| format!(" {}", line))
+            .collect::<Vec<_>>();
+        let program_docs = if program_docs.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                #![doc = ""]
+                #(#![doc = #program_docs])*
+            }
+        };
+
+        let events = idl.events.as_ref().map(|events| {
+            let event_stream = generate_events(events, self.serde);
+            quote! {
+                pub mod events {
+                    //! Events emitted by the program.
+                    use super::*;
+                    #event_stream
+                }
+
+                pub use events::*;
+            }
+        });
+
+        let constants = if idl.constants.is_empty() {
+            quote! {}
+        } else {
+            let constants = generate_constants(&idl.constants)?;
+            quote! {
+                pub mod constants {
+                    //! Constants defined by the program.
+                    use super::*;
+                    #constants
+                }
+            }
+        };
+
+        let errors = idl
+            .errors
+            .as_ref()
+            .filter(|errors| !errors.is_empty())
+            .map(|errors| {
+                let error_code = generate_error_code(errors);
+                quote! {
+                    pub mod errors {
+                        //! Errors returned by the program.
+                        use super::*;
+                        #error_code
+                    }
+
+                    // Shadows the `ErrorCode` of the Anchor prelude.
+                    pub use errors::ErrorCode;
+                }
+            });
+
+        let ix_serdes = if self.serde {
+            let ix_serdes = generate_ix_serdes(&idl.instructions);
+            quote! {
+                mod ix_serde {
+                    //! Serde implementations of the arguments of instructions.
+                    use super::*;
+                    #ix_serdes
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        Ok(quote! {
             use anchor_lang::prelude::*;
 
             pub mod typedefs {
@@ -106,8 +279,26 @@ impl Generator {
                 //! Accounts used in instructions.
                 use super::*;
                 #ix_structs
+                #ix_account_defaults
+            }
+
+            pub mod ix_builders {
+                //! Builders of instructions for off-chain clients.
+                use super::*;
+                #ix_builders
+            }
+
+            pub mod pda {
+                //! Program derived addresses of instruction accounts.
+                use super::*;
+                #pdas
             }
 
+            #constants
+            #events
+            #errors
+            #ix_serdes
+
             use ix_accounts::*;
             pub use state::*;
             pub use typedefs::*;
@@ -115,92 +306,123 @@ impl Generator {
             #[program]
             pub mod #program_name {
                 #![doc = #docs]
+                #program_docs
 
                 use super::*;
                 #ix_handlers
             }
-        }
+        })
     }
 
-    pub fn generate_cpi_interface(&self) -> TokenStream {
-        let idl = &self.idl;
-        let program_name: Ident = format_ident!("{}", idl.name);
+    /// Generates a CPI crate: the CPI interface, along with the `AccountType`, `InstructionType`
+    /// and `EventType` enums which decode the data of the program.
+    pub fn generate_cpi_crate(&self) -> GeneratorResult<TokenStream> {
+        let mut ts = self.generate_cpi_interface()?;
+        let serde = generate_type_enum_serde(self.serde);
 
-        let accounts = generate_accounts(&idl.types, &idl.accounts, &self.struct_opts);
-        let typedefs = generate_typedefs(&idl.types, &self.struct_opts);
-        let ix_handlers = generate_ix_handlers(&idl.instructions);
-        let ix_structs = generate_ix_structs(&idl.instructions);
-
-        let docs = format!(
-            " Anchor CPI crate generated from {} v{} using [anchor-gen](https://crates.io/crates/anchor-gen) v{}.",
-            &idl.name,
-            &idl.version,
-            &GEN_VERSION.unwrap_or("unknown")
-        );
-
-        let token_stream = quote! {
-            use anchor_lang::prelude::*;
-
-            pub mod typedefs {
-                //! User-defined types.
-                use super::*;
-                #typedefs
-            }
-
-            pub mod state {
-                //! Structs of accounts which hold state.
-                use super::*;
-                #accounts
-            }
-
-            pub mod ix_accounts {
-                //! Accounts used in instructions.
-                use super::*;
-                #ix_structs
-            }
-        };
-
-        if let Some(events) = &idl.events {
-            let event_stream = generate_events(&events);
-
-            quote! {
-                #token_stream
-
-                pub mod events {
-                    //! Events emitted by the program.
-                    use super::*;
-                    #event_stream
+        let event_variants = self.event_types().into_iter().map(|ident| {
+            let variant_name = ident.clone();
+            let discrim = generate_discriminator(&self.discriminators.event(&ident.to_string()));
+            quote! { #variant_name(#ident) #discrim }
+        });
+        if event_variants.len() > 0 {
+            let event_ts = quote! {
+                anchor_gen::derive_event_type!(
+                    #serde
+                    pub enum EventType {
+                        #(#event_variants,)*
+                    }
+                );
+
+                impl EventType {
+                    /// Decodes the events emitted by this program in the log messages of a
+                    /// transaction, along with the depth of the invocation which emitted them.
+                    pub fn from_logs<L: AsRef<str>>(logs: &[L]) -> anchor_gen::LogEvents<'_, L, EventType> {
+                        anchor_gen::LogEvents::new(&ID, logs)
+                    }
                 }
+            };
+            ts.extend(event_ts);
+        }
 
-                use ix_accounts::*;
-                pub use state::*;
-                pub use events::*;
-                pub use typedefs::*;
-
-                #[program]
-                pub mod #program_name {
-                    #![doc = #docs]
+        let acct_variants = self.account_types().into_iter().map(|ident| {
+            let variant_name = ident.clone();
+            let discrim = generate_discriminator(&self.discriminators.account(&ident.to_string()));
+            let zero_copy = self
+                .struct_opts
+                .get(&ident.to_string())
+                .filter(|opts| opts.zero_copy)
+                .map(|_| quote! { #[zero_copy] });
+            quote! { #zero_copy #variant_name(#ident) #discrim }
+        });
+        if acct_variants.len() > 0 {
+            let account_ts = quote! {
+                anchor_gen::derive_account_type!(
+                    #serde
+                    pub enum AccountType {
+                        #(#acct_variants,)*
+                    }
+                );
+            };
+            ts.extend(account_ts);
+        }
 
-                    use super::*;
-                    #ix_handlers
-                }
-            }
-        } else {
-            quote! {
-                #token_stream
+        let ix_variants = self.idl.instructions.iter().map(|ix| {
+            let ident = format_ident!("{}", ix.name.to_pascal_case());
+            let discrim = generate_discriminator(&self.discriminators.instruction(&ix.name));
+            quote! { #ident(instruction::#ident) #discrim }
+        });
+        if ix_variants.len() > 0 {
+            let ix_ts = quote! {
+                anchor_gen::derive_instruction_type!(
+                    #serde
+                    pub enum InstructionType {
+                        #(#ix_variants,)*
+                    }
+                );
+            };
+            ts.extend(ix_ts);
+        }
 
-                use ix_accounts::*;
-                pub use state::*;
-                pub use typedefs::*;
+        Ok(ts)
+    }
 
-                #[program]
-                pub mod #program_name {
-                    #![doc = #docs]
+    /// Generates the source of a CPI crate, formatted and preceded by a header recording the IDL
+    /// and the version of anchor-gen it was generated from. The source is the expansion of
+    /// `generate_cpi_crate!`, so `declare_id!` must be called alongside it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anchor_idl::GeneratorOptions;
+    ///
+    /// let opts = GeneratorOptions {
+    ///     idl_path: "../../examples/govern-cpi/idl.json".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let source = opts.to_generator().unwrap().generate_source().unwrap();
+    /// assert!(source.starts_with("// Generated from the IDL of govern v0.5.6 by anchor-gen v"));
+    /// assert!(source.contains("\n    pub struct GovernanceParameters {\n"));
+    /// ```
+    pub fn generate_source(&self) -> GeneratorResult<String> {
+        let file: syn::File = syn::parse2(self.generate_cpi_crate()?).map_err(|err| {
+            GeneratorError::new(format!("generated code is not valid Rust: {}", err))
+        })?;
+        Ok(format!(
+            "// Generated from the IDL of {} v{} by anchor-gen v{}. Do not edit.\n\n{}",
+            self.idl.name,
+            self.idl.version,
+            GEN_VERSION.unwrap_or("unknown"),
+            prettyplease::unparse(&file)
+        ))
+    }
 
-                    use super::*;
-                    #ix_handlers
-                }
-            }
+    /// State accounts which instruction accounts may be typed as.
+    fn ix_account_types(&self) -> AccountTypes {
+        if self.untyped_accounts {
+            AccountTypes::default()
+        } else {
+            AccountTypes::new(&self.idl.accounts, &self.struct_opts)
         }
     }
 
@@ -227,11 +449,27 @@ impl Generator {
     pub fn event_types(&self) -> Vec<Ident> {
         let ix_idents: Vec<Ident> = match &self.idl.events {
             None => vec![],
-            Some(events) => events
-                .iter()
-                .map(|d| format_ident!("{}", d.name))
-                .collect(),
+            Some(events) => events.iter().map(|d| format_ident!("{}", d.name)).collect(),
         };
         ix_idents
     }
 }
+
+/// Renders the discriminator of a variant of the `derive_*_type!` macros, so that it is a constant
+/// rather than derived from the name of the type each time data is decoded.
+fn generate_discriminator(discrim: &[u8]) -> TokenStream {
+    quote! { = [#(#discrim),*] }
+}
+
+/// Renders the serde derives of the `AccountType`, `InstructionType` and `EventType` enums, which
+/// share a schema: the name of the variant under `type`, and its value under `data`.
+fn generate_type_enum_serde(serde: bool) -> TokenStream {
+    if serde {
+        quote! {
+            #[derive(anchor_gen::serde::Serialize, anchor_gen::serde::Deserialize)]
+            #[serde(crate = "anchor_gen::serde", tag = "type", content = "data")]
+        }
+    } else {
+        quote! {}
+    }
+}
@@ -0,0 +1,179 @@
+//! Serde representations of the fields of generated types which JSON cannot represent faithfully.
+//!
+//! The types of this module describe how a field is (de)serialized, and are used by the code
+//! generated with the `serde` option through `#[serde(serialize_with, deserialize_with)]`:
+//!
+//! ```
+//! use anchor_idl::serde_as::{self, DisplayFromStr, OptionOf};
+//! use anchor_lang::prelude::Pubkey;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Position {
+//!     #[serde(
+//!         serialize_with = "serde_as::serialize::<DisplayFromStr, _, _>",
+//!         deserialize_with = "serde_as::deserialize::<DisplayFromStr, _, _>"
+//!     )]
+//!     owner: Pubkey,
+//!     #[serde(
+//!         serialize_with = "serde_as::serialize::<OptionOf<DisplayFromStr>, _, _>",
+//!         deserialize_with = "serde_as::deserialize::<OptionOf<DisplayFromStr>, _, _>"
+//!     )]
+//!     amount: Option<u64>,
+//! }
+//!
+//! let position = Position {
+//!     owner: Pubkey::default(),
+//!     amount: Some(u64::MAX),
+//! };
+//! let json = serde_json::to_value(&position).unwrap();
+//! assert_eq!(
+//!     json,
+//!     serde_json::json!({
+//!         "owner": "11111111111111111111111111111111",
+//!         "amount": "18446744073709551615",
+//!     })
+//! );
+//! assert_eq!(serde_json::from_value::<Position>(json).unwrap(), position);
+//! ```
+
+use std::{fmt::Display, marker::PhantomData, str::FromStr};
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes values of type `T` in the representation `Self`.
+pub trait SerializeAs<T: ?Sized> {
+    fn serialize_as<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// Deserializes values of type `T` from the representation `Self`.
+pub trait DeserializeAs<'de, T>: Sized {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error>;
+}
+
+/// Serializes `value` as `M`, for use in `#[serde(serialize_with)]`.
+pub fn serialize<M: SerializeAs<T>, T: ?Sized, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    M::serialize_as(value, serializer)
+}
+
+/// Deserializes a value as `M`, for use in `#[serde(deserialize_with)]`.
+pub fn deserialize<'de, M: DeserializeAs<'de, T>, T, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    M::deserialize_as(deserializer)
+}
+
+/// The [Serialize] and [Deserialize] implementations of the type itself.
+pub struct Same;
+
+impl<T: Serialize> SerializeAs<T> for Same {
+    fn serialize_as<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeAs<'de, T> for Same {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        T::deserialize(deserializer)
+    }
+}
+
+/// A string, through [Display] and [FromStr]: public keys are rendered in base58, and integers
+/// which do not fit in a double in decimal.
+pub struct DisplayFromStr;
+
+impl<T: Display> SerializeAs<T> for DisplayFromStr {
+    fn serialize_as<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+}
+
+impl<'de, T> DeserializeAs<'de, T> for DisplayFromStr
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+/// An [Option] whose value is represented as `M`.
+pub struct OptionOf<M>(PhantomData<M>);
+
+impl<T, M: SerializeAs<T>> SerializeAs<Option<T>> for OptionOf<M> {
+    fn serialize_as<S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(As::<M, T>::new).serialize(serializer)
+    }
+}
+
+impl<'de, T, M: DeserializeAs<'de, T>> DeserializeAs<'de, Option<T>> for OptionOf<M> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+        let value = Option::<DeAs<M, T>>::deserialize(deserializer)?;
+        Ok(value.map(|value| value.0))
+    }
+}
+
+/// A [Vec] whose elements are represented as `M`.
+pub struct VecOf<M>(PhantomData<M>);
+
+impl<T, M: SerializeAs<T>> SerializeAs<Vec<T>> for VecOf<M> {
+    fn serialize_as<S: Serializer>(value: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(As::<M, T>::new))
+    }
+}
+
+impl<'de, T, M: DeserializeAs<'de, T>> DeserializeAs<'de, Vec<T>> for VecOf<M> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> {
+        let values = Vec::<DeAs<M, T>>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|value| value.0).collect())
+    }
+}
+
+/// An array of any length whose elements are represented as `M`, as serde only implements its
+/// traits for arrays of at most 32 elements.
+pub struct ArrayOf<M>(PhantomData<M>);
+
+impl<T, M: SerializeAs<T>, const N: usize> SerializeAs<[T; N]> for ArrayOf<M> {
+    fn serialize_as<S: Serializer>(value: &[T; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(As::<M, T>::new))
+    }
+}
+
+impl<'de, T, M: DeserializeAs<'de, T>, const N: usize> DeserializeAs<'de, [T; N]> for ArrayOf<M> {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<[T; N], D::Error> {
+        let values = VecOf::<M>::deserialize_as(deserializer)?;
+        let len = values.len();
+        values.try_into().map_err(|_| {
+            D::Error::invalid_length(len, &format!("an array of {} elements", N).as_str())
+        })
+    }
+}
+
+/// A reference to a value which serializes as `M`.
+struct As<'a, M, T>(&'a T, PhantomData<M>);
+
+impl<'a, M, T> As<'a, M, T> {
+    fn new(value: &'a T) -> Self {
+        As(value, PhantomData)
+    }
+}
+
+impl<M: SerializeAs<T>, T> Serialize for As<'_, M, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        M::serialize_as(self.0, serializer)
+    }
+}
+
+/// A value which deserializes as `M`.
+struct DeAs<M, T>(T, PhantomData<M>);
+
+impl<'de, M: DeserializeAs<'de, T>, T> Deserialize<'de> for DeAs<M, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        M::deserialize_as(deserializer).map(|value| DeAs(value, PhantomData))
+    }
+}
@@ -0,0 +1,704 @@
+//! Loading of IDLs in both the legacy (Anchor <= 0.29) and the new (Anchor >= 0.30) formats.
+//!
+//! Both formats are converted into the legacy [Idl] model, which is what the code generators consume.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang_idl_spec as new;
+use anchor_syn::idl::types::{
+    EnumFields, Idl, IdlAccount, IdlAccountItem, IdlAccounts, IdlConst, IdlDefinedTypeArg,
+    IdlEnumVariant, IdlErrorCode, IdlEvent, IdlEventField, IdlField, IdlInstruction, IdlPda,
+    IdlSeed, IdlSeedAccount, IdlSeedArg, IdlSeedConst, IdlType, IdlTypeDefinition,
+    IdlTypeDefinitionTy,
+};
+
+use crate::{
+    parse_constant, validate_discriminators, validate_errors, validate_idl, GeneratorError,
+    GeneratorResult, IdlDiscriminators, StructOpts,
+};
+
+/// Format of an IDL file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdlFormat {
+    /// IDLs generated by Anchor 0.29 and below.
+    Legacy,
+    /// IDLs following the specification introduced in Anchor 0.30.
+    Spec,
+}
+
+impl IdlFormat {
+    /// Detects the format of a parsed IDL JSON document.
+    ///
+    /// IDLs following the new specification have a top-level `address` and a `metadata.spec` field.
+    pub fn detect(value: &serde_json::Value) -> IdlFormat {
+        let has_address = value.get("address").is_some();
+        let has_spec = value
+            .get("metadata")
+            .and_then(|metadata| metadata.get("spec"))
+            .is_some();
+        if has_address && has_spec {
+            IdlFormat::Spec
+        } else {
+            IdlFormat::Legacy
+        }
+    }
+}
+
+/// An IDL converted into the model used by the code generators.
+#[derive(Clone, Debug)]
+pub struct LoadedIdl {
+    /// Format the IDL was written in.
+    pub format: IdlFormat,
+    /// The IDL.
+    pub idl: Idl,
+    /// Struct options recorded in the IDL, i.e. `serialization` and `repr`.
+    pub struct_opts: BTreeMap<String, StructOpts>,
+    /// Discriminators recorded in the IDL.
+    pub discriminators: IdlDiscriminators,
+    /// Addresses of instruction accounts recorded in the IDL.
+    pub addresses: IdlAddresses,
+}
+
+/// Addresses of instruction accounts which are fixed by the IDL, such as that of the system
+/// program.
+///
+/// IDLs following the Anchor 0.30 specification record them as the `address` of the account.
+/// Accounts are keyed by the name of their instruction, then by their name; the accounts of a
+/// nested account struct are keyed by the name of the struct and that of the account, e.g.
+/// `"group.account"`.
+#[derive(Clone, Debug, Default)]
+pub struct IdlAddresses {
+    pub instructions: BTreeMap<String, BTreeMap<String, Pubkey>>,
+}
+
+impl IdlAddresses {
+    /// Address of the account `account` of the instruction `ix`, if the IDL fixes it.
+    pub fn account(&self, ix: &str, account: &str) -> Option<Pubkey> {
+        self.instructions.get(ix)?.get(account).copied()
+    }
+}
+
+/// Parses and validates an IDL in either format.
+///
+/// Errors point to the JSON path of the offending node in the document, whichever its format.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{parse_idl, IdlFormat};
+///
+/// let loaded = parse_idl(
+///     r#"{
+///         "address": "Counter111111111111111111111111111111111111",
+///         "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+///         "instructions": [],
+///         "accounts": [{ "name": "Counter", "discriminator": [255, 176, 4, 245, 188, 253, 124, 25] }],
+///         "types": [{
+///             "name": "Counter",
+///             "type": { "kind": "struct", "fields": [{ "name": "authority", "type": "pubkey" }] }
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(loaded.format, IdlFormat::Spec);
+/// assert_eq!(loaded.idl.name, "counter");
+/// assert_eq!(loaded.idl.accounts[0].name, "Counter");
+/// assert!(loaded.idl.types.is_empty());
+/// assert_eq!(
+///     loaded.discriminators.account("Counter"),
+///     vec![255, 176, 4, 245, 188, 253, 124, 25]
+/// );
+///
+/// // `Mode` is the second of `types`, although the converted IDL only has one type.
+/// let err = parse_idl(
+///     r#"{
+///         "address": "Counter111111111111111111111111111111111111",
+///         "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+///         "instructions": [],
+///         "accounts": [{ "name": "Counter", "discriminator": [255, 176, 4, 245, 188, 253, 124, 25] }],
+///         "types": [
+///             { "name": "Counter", "type": { "kind": "struct", "fields": [] } },
+///             {
+///                 "name": "Mode",
+///                 "type": { "kind": "struct", "fields": [{ "name": "side", "type": { "defined": { "name": "Side" } } }] }
+///             }
+///         ]
+///     }"#,
+/// )
+/// .unwrap_err();
+/// assert_eq!(err.path.as_deref(), Some("types[1].type.fields[0].type.defined.name"));
+/// ```
+pub fn parse_idl(contents: &str) -> GeneratorResult<LoadedIdl> {
+    let value: serde_json::Value = serde_json::from_str(contents)
+        .map_err(|e| GeneratorError::new(format!("IDL is not valid JSON: {}", e)))?;
+    let loaded = match IdlFormat::detect(&value) {
+        IdlFormat::Legacy => {
+            let idl: Idl = serde_path_to_error::deserialize(value)?;
+            validate_idl(&idl)?;
+            LoadedIdl {
+                format: IdlFormat::Legacy,
+                idl,
+                struct_opts: BTreeMap::new(),
+                discriminators: IdlDiscriminators::default(),
+                addresses: IdlAddresses::default(),
+            }
+        }
+        IdlFormat::Spec => {
+            let idl: new::Idl = serde_path_to_error::deserialize(value)?;
+            convert_idl(&idl)?
+        }
+    };
+    validate_discriminators(&loaded.idl, &loaded.discriminators)?;
+    Ok(loaded)
+}
+
+/// Converts an IDL following the new specification into the legacy model.
+///
+/// Account and event structs are moved out of `types` into `accounts` and `events`, as is the
+/// case in legacy IDLs. As this changes the paths of their nodes, the IDL is validated while it is
+/// converted, with the checks of [validate_idl], so that errors point to the original nodes.
+pub fn convert_idl(idl: &new::Idl) -> GeneratorResult<LoadedIdl> {
+    let find_type = |name: &str, path: String| {
+        idl.types
+            .iter()
+            .position(|ty| ty.name == name)
+            .map(|i| (&idl.types[i], format!("types[{}]", i)))
+            .ok_or_else(|| GeneratorError::at(path, format!("type {} is not defined", name)))
+    };
+
+    let accounts = idl
+        .accounts
+        .iter()
+        .enumerate()
+        .map(|(i, account)| {
+            let (def, path) = find_type(&account.name, format!("accounts[{}].name", i))?;
+            convert_typedef(idl, def, &path)
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    let events = idl
+        .events
+        .iter()
+        .enumerate()
+        .map(|(i, event)| {
+            let (def, path) = find_type(&event.name, format!("events[{}].name", i))?;
+            convert_event(idl, def, &path)
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    let types = idl
+        .types
+        .iter()
+        .enumerate()
+        .filter(|(_, ty)| {
+            !idl.accounts.iter().any(|account| account.name == ty.name)
+                && !idl.events.iter().any(|event| event.name == ty.name)
+        })
+        .map(|(i, ty)| convert_typedef(idl, ty, &format!("types[{}]", i)))
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    let mut addresses = IdlAddresses::default();
+    let instructions = idl
+        .instructions
+        .iter()
+        .enumerate()
+        .map(|(i, ix)| {
+            let mut ix_addresses = BTreeMap::new();
+            let converted =
+                convert_instruction(idl, ix, &format!("instructions[{}]", i), &mut ix_addresses)?;
+            if !ix_addresses.is_empty() {
+                addresses.instructions.insert(ix.name.clone(), ix_addresses);
+            }
+            Ok(converted)
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    let constants = idl
+        .constants
+        .iter()
+        .enumerate()
+        .map(|(i, constant)| {
+            let converted = IdlConst {
+                name: constant.name.clone(),
+                ty: convert_type(idl, &constant.ty, &format!("constants[{}].type", i))?,
+                value: constant.value.clone(),
+            };
+            parse_constant(i, &converted)?;
+            Ok(converted)
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    let errors = idl
+        .errors
+        .iter()
+        .map(|error| IdlErrorCode {
+            code: error.code,
+            name: error.name.clone(),
+            msg: error.msg.clone(),
+        })
+        .collect::<Vec<_>>();
+    validate_errors(&errors)?;
+
+    let struct_opts = idl
+        .types
+        .iter()
+        .filter_map(|ty| {
+            let zero_copy = matches!(
+                ty.serialization,
+                new::IdlSerialization::Bytemuck | new::IdlSerialization::BytemuckUnsafe
+            );
+            let packed = matches!(
+                &ty.repr,
+                Some(new::IdlRepr::C(modifier)) | Some(new::IdlRepr::Rust(modifier)) if modifier.packed
+            );
+            if zero_copy || packed {
+                Some((ty.name.clone(), StructOpts { packed, zero_copy }))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let discriminators = IdlDiscriminators {
+        accounts: idl
+            .accounts
+            .iter()
+            .map(|account| (account.name.clone(), account.discriminator.clone()))
+            .collect(),
+        instructions: idl
+            .instructions
+            .iter()
+            .map(|ix| (ix.name.clone(), ix.discriminator.clone()))
+            .collect(),
+        events: idl
+            .events
+            .iter()
+            .map(|event| (event.name.clone(), event.discriminator.clone()))
+            .collect(),
+    };
+
+    Ok(LoadedIdl {
+        format: IdlFormat::Spec,
+        idl: Idl {
+            version: idl.metadata.version.clone(),
+            name: idl.metadata.name.clone(),
+            docs: non_empty(&idl.docs),
+            constants,
+            instructions,
+            accounts,
+            types,
+            events: if events.is_empty() {
+                None
+            } else {
+                Some(events)
+            },
+            errors: if errors.is_empty() {
+                None
+            } else {
+                Some(errors)
+            },
+            metadata: Some(serde_json::json!({
+                "address": idl.address,
+                "spec": idl.metadata.spec,
+            })),
+        },
+        struct_opts,
+        discriminators,
+        addresses,
+    })
+}
+
+fn non_empty(docs: &[String]) -> Option<Vec<String>> {
+    if docs.is_empty() {
+        None
+    } else {
+        Some(docs.to_vec())
+    }
+}
+
+fn convert_type(idl: &new::Idl, ty: &new::IdlType, path: &str) -> GeneratorResult<IdlType> {
+    Ok(match ty {
+        new::IdlType::Bool => IdlType::Bool,
+        new::IdlType::U8 => IdlType::U8,
+        new::IdlType::I8 => IdlType::I8,
+        new::IdlType::U16 => IdlType::U16,
+        new::IdlType::I16 => IdlType::I16,
+        new::IdlType::U32 => IdlType::U32,
+        new::IdlType::I32 => IdlType::I32,
+        new::IdlType::F32 => IdlType::F32,
+        new::IdlType::U64 => IdlType::U64,
+        new::IdlType::I64 => IdlType::I64,
+        new::IdlType::F64 => IdlType::F64,
+        new::IdlType::U128 => IdlType::U128,
+        new::IdlType::I128 => IdlType::I128,
+        new::IdlType::U256 => IdlType::U256,
+        new::IdlType::I256 => IdlType::I256,
+        new::IdlType::Bytes => IdlType::Bytes,
+        new::IdlType::String => IdlType::String,
+        new::IdlType::Pubkey => IdlType::PublicKey,
+        new::IdlType::Option(inner) => IdlType::Option(Box::new(convert_type(
+            idl,
+            inner,
+            &format!("{}.option", path),
+        )?)),
+        new::IdlType::Vec(inner) => IdlType::Vec(Box::new(convert_type(
+            idl,
+            inner,
+            &format!("{}.vec", path),
+        )?)),
+        new::IdlType::Array(inner, new::IdlArrayLen::Value(len)) => IdlType::Array(
+            Box::new(convert_type(idl, inner, &format!("{}.array[0]", path))?),
+            *len,
+        ),
+        new::IdlType::Array(inner, new::IdlArrayLen::Generic(len)) => IdlType::GenericLenArray(
+            Box::new(convert_type(idl, inner, &format!("{}.array[0]", path))?),
+            len.clone(),
+        ),
+        new::IdlType::Defined { name, .. } if !idl.types.iter().any(|def| def.name == *name) => {
+            return Err(GeneratorError::at(
+                format!("{}.defined.name", path),
+                format!("type {} is not defined", name),
+            ))
+        }
+        new::IdlType::Defined { name, generics } if generics.is_empty() => {
+            IdlType::Defined(name.clone())
+        }
+        new::IdlType::Defined { name, generics } => IdlType::DefinedWithTypeArgs {
+            name: name.clone(),
+            args: generics
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| match arg {
+                    new::IdlGenericArg::Type { ty } => Ok(IdlDefinedTypeArg::Type(convert_type(
+                        idl,
+                        ty,
+                        &format!("{}.defined.generics[{}].type", path, i),
+                    )?)),
+                    new::IdlGenericArg::Const { value } => {
+                        Ok(IdlDefinedTypeArg::Value(value.clone()))
+                    }
+                })
+                .collect::<GeneratorResult<Vec<_>>>()?,
+        },
+        new::IdlType::Generic(name) => IdlType::Generic(name.clone()),
+        other => {
+            return Err(GeneratorError::at(
+                path,
+                format!("unsupported type {:?}", other),
+            ))
+        }
+    })
+}
+
+fn convert_fields(
+    idl: &new::Idl,
+    fields: &[new::IdlField],
+    path: &str,
+) -> GeneratorResult<Vec<IdlField>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            Ok(IdlField {
+                name: field.name.clone(),
+                docs: non_empty(&field.docs),
+                ty: convert_type(idl, &field.ty, &format!("{}[{}].type", path, i))?,
+            })
+        })
+        .collect()
+}
+
+fn convert_typedef(
+    idl: &new::Idl,
+    def: &new::IdlTypeDef,
+    path: &str,
+) -> GeneratorResult<IdlTypeDefinition> {
+    if let new::IdlSerialization::Custom(serialization) = &def.serialization {
+        return Err(GeneratorError::at(
+            format!("{}.serialization", path),
+            format!(
+                "custom serialization {} of {} is not supported",
+                serialization, def.name
+            ),
+        ));
+    }
+    let ty = match &def.ty {
+        new::IdlTypeDefTy::Struct { fields: None } => {
+            IdlTypeDefinitionTy::Struct { fields: vec![] }
+        }
+        new::IdlTypeDefTy::Struct {
+            fields: Some(new::IdlDefinedFields::Named(fields)),
+        } => IdlTypeDefinitionTy::Struct {
+            fields: convert_fields(idl, fields, &format!("{}.type.fields", path))?,
+        },
+        new::IdlTypeDefTy::Struct {
+            fields: Some(new::IdlDefinedFields::Tuple(_)),
+        } => {
+            return Err(GeneratorError::at(
+                format!("{}.type.fields", path),
+                format!("tuple struct {} is not supported", def.name),
+            ))
+        }
+        new::IdlTypeDefTy::Enum { variants } if variants.is_empty() => {
+            return Err(GeneratorError::at(
+                format!("{}.type.variants", path),
+                format!("enum {} has no variants", def.name),
+            ))
+        }
+        new::IdlTypeDefTy::Enum { variants } => IdlTypeDefinitionTy::Enum {
+            variants: variants
+                .iter()
+                .enumerate()
+                .map(|(i, variant)| {
+                    let fields_path = format!("{}.type.variants[{}].fields", path, i);
+                    Ok(IdlEnumVariant {
+                        name: variant.name.clone(),
+                        fields: match &variant.fields {
+                            None => None,
+                            Some(new::IdlDefinedFields::Named(fields)) => Some(EnumFields::Named(
+                                convert_fields(idl, fields, &fields_path)?,
+                            )),
+                            Some(new::IdlDefinedFields::Tuple(types)) => Some(EnumFields::Tuple(
+                                types
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(j, ty)| {
+                                        convert_type(idl, ty, &format!("{}[{}]", fields_path, j))
+                                    })
+                                    .collect::<GeneratorResult<_>>()?,
+                            )),
+                        },
+                    })
+                })
+                .collect::<GeneratorResult<Vec<_>>>()?,
+        },
+        new::IdlTypeDefTy::Type { alias } => IdlTypeDefinitionTy::Alias {
+            value: convert_type(idl, alias, &format!("{}.type.alias", path))?,
+        },
+    };
+    Ok(IdlTypeDefinition {
+        name: def.name.clone(),
+        docs: non_empty(&def.docs),
+        generics: if def.generics.is_empty() {
+            None
+        } else {
+            Some(
+                def.generics
+                    .iter()
+                    .map(|generic| match generic {
+                        new::IdlTypeDefGeneric::Type { name } => name.clone(),
+                        new::IdlTypeDefGeneric::Const { name, .. } => name.clone(),
+                    })
+                    .collect(),
+            )
+        },
+        ty,
+    })
+}
+
+fn convert_event(idl: &new::Idl, def: &new::IdlTypeDef, path: &str) -> GeneratorResult<IdlEvent> {
+    match convert_typedef(idl, def, path)?.ty {
+        IdlTypeDefinitionTy::Struct { fields } => Ok(IdlEvent {
+            name: def.name.clone(),
+            fields: fields
+                .into_iter()
+                .map(|field| IdlEventField {
+                    name: field.name,
+                    ty: field.ty,
+                    index: false,
+                })
+                .collect(),
+        }),
+        _ => Err(GeneratorError::at(
+            path,
+            format!("event {} is not a struct", def.name),
+        )),
+    }
+}
+
+/// Converts an instruction, collecting the addresses of its accounts into `addresses`.
+fn convert_instruction(
+    idl: &new::Idl,
+    ix: &new::IdlInstruction,
+    path: &str,
+    addresses: &mut BTreeMap<String, Pubkey>,
+) -> GeneratorResult<IdlInstruction> {
+    let args = convert_fields(idl, &ix.args, &format!("{}.args", path))?;
+    Ok(IdlInstruction {
+        name: ix.name.clone(),
+        docs: non_empty(&ix.docs),
+        accounts: convert_account_items(
+            idl,
+            &args,
+            &ix.accounts,
+            &format!("{}.accounts", path),
+            "",
+            addresses,
+        )?,
+        args,
+        returns: ix
+            .returns
+            .as_ref()
+            .map(|ty| convert_type(idl, ty, &format!("{}.returns", path)))
+            .transpose()?,
+    })
+}
+
+fn convert_account_items(
+    idl: &new::Idl,
+    args: &[IdlField],
+    items: &[new::IdlInstructionAccountItem],
+    path: &str,
+    prefix: &str,
+    addresses: &mut BTreeMap<String, Pubkey>,
+) -> GeneratorResult<Vec<IdlAccountItem>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let item_path = format!("{}[{}]", path, i);
+            Ok(match item {
+                new::IdlInstructionAccountItem::Single(account) => {
+                    if let Some(address) = &account.address {
+                        let address = Pubkey::from_str(address).map_err(|_| {
+                            GeneratorError::at(
+                                format!("{}.address", item_path),
+                                format!("invalid public key `{}`", address),
+                            )
+                        })?;
+                        addresses.insert(format!("{}{}", prefix, account.name), address);
+                    }
+                    IdlAccountItem::IdlAccount(IdlAccount {
+                        name: account.name.clone(),
+                        is_mut: account.writable,
+                        is_signer: account.signer,
+                        is_optional: if account.optional { Some(true) } else { None },
+                        docs: non_empty(&account.docs),
+                        pda: account
+                            .pda
+                            .as_ref()
+                            .map(|pda| convert_pda(idl, args, pda, &format!("{}.pda", item_path)))
+                            .transpose()?
+                            .flatten(),
+                        relations: account.relations.clone(),
+                    })
+                }
+                new::IdlInstructionAccountItem::Composite(accounts) => {
+                    IdlAccountItem::IdlAccounts(IdlAccounts {
+                        name: accounts.name.clone(),
+                        accounts: convert_account_items(
+                            idl,
+                            args,
+                            &accounts.accounts,
+                            &format!("{}.accounts", item_path),
+                            &format!("{}{}.", prefix, accounts.name),
+                            addresses,
+                        )?,
+                    })
+                }
+            })
+        })
+        .collect()
+}
+
+/// Converts the seeds of a PDA, or returns `None` if the type of a seed cannot be known, in which
+/// case no helper is generated for the account.
+fn convert_pda(
+    idl: &new::Idl,
+    args: &[IdlField],
+    pda: &new::IdlPda,
+    path: &str,
+) -> GeneratorResult<Option<IdlPda>> {
+    let seeds = pda
+        .seeds
+        .iter()
+        .enumerate()
+        .map(|(i, seed)| convert_seed(idl, args, seed, &format!("{}.seeds[{}]", path, i)))
+        .collect::<GeneratorResult<Option<Vec<_>>>>()?;
+    let program_id = match &pda.program {
+        Some(seed) => match convert_seed(idl, args, seed, &format!("{}.program", path))? {
+            Some(seed) => Some(seed),
+            None => return Ok(None),
+        },
+        None => None,
+    };
+    Ok(seeds.map(|seeds| IdlPda { seeds, program_id }))
+}
+
+/// Converts a PDA seed. Seeds of the new specification do not record their type, so it is
+/// resolved from the instruction arguments or the referenced account's fields. Fields of accounts
+/// whose type is not recorded cannot be resolved, and yield `None`.
+fn convert_seed(
+    idl: &new::Idl,
+    args: &[IdlField],
+    seed: &new::IdlSeed,
+    path: &str,
+) -> GeneratorResult<Option<IdlSeed>> {
+    Ok(Some(match seed {
+        new::IdlSeed::Const(seed) => IdlSeed::Const(IdlSeedConst {
+            ty: IdlType::Bytes,
+            value: serde_json::json!(seed.value),
+        }),
+        new::IdlSeed::Arg(seed) => {
+            let mut segments = seed.path.split('.');
+            let root = segments.next().unwrap_or_default();
+            let root_ty = args
+                .iter()
+                .find(|arg| arg.name == root)
+                .map(|arg| arg.ty.clone())
+                .ok_or_else(|| {
+                    GeneratorError::at(
+                        format!("{}.path", path),
+                        format!("seed references unknown argument {}", seed.path),
+                    )
+                })?;
+            IdlSeed::Arg(IdlSeedArg {
+                ty: resolve_field_path(idl, root_ty, segments)
+                    .map_err(|message| GeneratorError::at(format!("{}.path", path), message))?,
+                path: seed.path.clone(),
+            })
+        }
+        new::IdlSeed::Account(seed) => {
+            let mut segments = seed.path.split('.').skip(1).peekable();
+            let ty = match (&seed.account, segments.peek()) {
+                // A seed naming an account alone is its address.
+                (_, None) => IdlType::PublicKey,
+                (Some(account), Some(_)) => {
+                    resolve_field_path(idl, IdlType::Defined(account.clone()), segments)
+                        .map_err(|message| GeneratorError::at(format!("{}.path", path), message))?
+                }
+                (None, Some(_)) => return Ok(None),
+            };
+            IdlSeed::Account(IdlSeedAccount {
+                ty,
+                account: seed.account.clone(),
+                path: seed.path.clone(),
+            })
+        }
+    }))
+}
+
+/// Resolves the type of a field nested in `ty`, following the `.`-separated `path`.
+fn resolve_field_path<'a>(
+    idl: &new::Idl,
+    ty: IdlType,
+    mut path: impl Iterator<Item = &'a str>,
+) -> Result<IdlType, String> {
+    path.try_fold(ty, |ty, segment| {
+        let name = match ty {
+            IdlType::Defined(name) => name,
+            other => return Err(format!("cannot access field {} of {:?}", segment, other)),
+        };
+        let def = idl
+            .types
+            .iter()
+            .find(|def| def.name == name)
+            .ok_or_else(|| format!("type {} is not defined", name))?;
+        match &def.ty {
+            new::IdlTypeDefTy::Struct {
+                fields: Some(new::IdlDefinedFields::Named(fields)),
+            } => fields
+                .iter()
+                .find(|field| field.name == segment)
+                .ok_or_else(|| format!("type {} has no field {}", name, segment))
+                .and_then(|field| convert_type(idl, &field.ty, segment).map_err(|err| err.message)),
+            _ => Err(format!("type {} has no field {}", name, segment)),
+        }
+    })
+}
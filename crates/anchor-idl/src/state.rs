@@ -3,8 +3,11 @@ use std::collections::BTreeMap;
 use anchor_syn::idl::types::{IdlField, IdlTypeDefinition, IdlTypeDefinitionTy};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
+use syn::Generics;
 
-use crate::{generate_fields, get_field_list_properties, StructOpts};
+use crate::{
+    generate_fields, generate_serde_derive, get_field_list_properties, GeneratorResult, StructOpts,
+};
 
 /// Generates an account state struct.
 pub fn generate_account(
@@ -12,8 +15,9 @@ pub fn generate_account(
     account_name: &str,
     fields: &[IdlField],
     opts: StructOpts,
-) -> TokenStream {
-    let props = get_field_list_properties(defs, fields);
+    serde: bool,
+) -> GeneratorResult<TokenStream> {
+    let props = get_field_list_properties(defs, fields)?;
 
     let derive_copy = if props.can_copy && !opts.zero_copy {
         quote! {
@@ -30,9 +34,11 @@ pub fn generate_account(
         quote! {}
     };
     let derive_account = if opts.zero_copy {
+        // Fields keep the order of the IDL only with `repr(C)`, which the `Pod` derive of
+        // `zero_copy` also requires, so packed structs are `repr(C, packed)`.
         let repr = if opts.packed {
             quote! {
-                #[repr(packed)]
+                #[repr(C, packed)]
             }
         } else {
             quote! {
@@ -51,16 +57,18 @@ pub fn generate_account(
 
     let doc = format!(" Account: {}", account_name);
     let struct_name = format_ident!("{}", account_name);
-    let fields_rendered = generate_fields(fields);
-    quote! {
+    let fields_rendered = generate_fields(fields, serde);
+    let derive_serde = generate_serde_derive(&Generics::default(), serde);
+    Ok(quote! {
         #derive_account
         #[doc = #doc]
         #derive_copy
         #derive_default
+        #derive_serde
         pub struct #struct_name {
             #fields_rendered
         }
-    }
+    })
 }
 
 /// Generates account state structs.
@@ -68,20 +76,26 @@ pub fn generate_accounts(
     typedefs: &[IdlTypeDefinition],
     account_defs: &[IdlTypeDefinition],
     struct_opts: &BTreeMap<String, StructOpts>,
-) -> TokenStream {
-    let defined = account_defs.iter().map(|def| match &def.ty {
-        IdlTypeDefinitionTy::Struct { fields } => {
-            let opts = struct_opts.get(&def.name).copied().unwrap_or_default();
-            generate_account(typedefs, &def.name, fields, opts)
-        }
-        IdlTypeDefinitionTy::Enum { .. } => {
-            quote! {}
-        }
-        IdlTypeDefinitionTy::Alias { .. } => {
-            quote! {}
-        }
-    });
-    quote! {
+    serde: bool,
+) -> GeneratorResult<TokenStream> {
+    let defs = [typedefs, account_defs].concat();
+    let defined = account_defs
+        .iter()
+        .map(|def| match &def.ty {
+            IdlTypeDefinitionTy::Struct { fields } => {
+                let opts = struct_opts.get(&def.name).copied().unwrap_or_default();
+                let docs = crate::generate_docs(def.docs.as_deref());
+                let account = generate_account(&defs, &def.name, fields, opts, serde)?;
+                Ok(quote! {
+                    #docs
+                    #account
+                })
+            }
+            IdlTypeDefinitionTy::Enum { .. } => Ok(quote! {}),
+            IdlTypeDefinitionTy::Alias { .. } => Ok(quote! {}),
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    Ok(quote! {
         #(#defined)*
-    }
+    })
 }
@@ -1,14 +1,15 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use anchor_syn::idl::types::{
-    EnumFields, IdlEnumVariant, IdlEvent, IdlEventField, IdlField, IdlType, IdlTypeDefinition,
-    IdlTypeDefinitionTy,
+    EnumFields, IdlDefinedTypeArg, IdlEnumVariant, IdlEvent, IdlEventField, IdlField, IdlType,
+    IdlTypeDefinition, IdlTypeDefinitionTy,
 };
 use heck::ToSnakeCase;
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
+use syn::{parse_quote, Generics};
 
-use crate::StructOpts;
+use crate::{GeneratorError, GeneratorResult, StructOpts};
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct FieldListProperties {
@@ -19,7 +20,7 @@ pub struct FieldListProperties {
 pub fn get_field_list_properties(
     defs: &[IdlTypeDefinition],
     fields: &[IdlField],
-) -> FieldListProperties {
+) -> GeneratorResult<FieldListProperties> {
     get_type_list_properties(
         defs,
         &fields.iter().map(|f| f.ty.clone()).collect::<Vec<_>>(),
@@ -29,20 +30,20 @@ pub fn get_field_list_properties(
 pub fn get_type_list_properties(
     defs: &[IdlTypeDefinition],
     fields: &[IdlType],
-) -> FieldListProperties {
-    fields.iter().fold(
+) -> GeneratorResult<FieldListProperties> {
+    fields.iter().try_fold(
         FieldListProperties {
             can_copy: true,
             can_derive_default: true,
         },
         |acc, el| {
-            let inner_props = get_type_properties(defs, el);
+            let inner_props = get_type_properties(defs, el)?;
             let can_copy = acc.can_copy && inner_props.can_copy;
             let can_derive_default = acc.can_derive_default && inner_props.can_derive_default;
-            FieldListProperties {
+            Ok(FieldListProperties {
                 can_copy,
                 can_derive_default,
-            }
+            })
         },
     )
 }
@@ -50,28 +51,43 @@ pub fn get_type_list_properties(
 pub fn get_variant_list_properties(
     defs: &[IdlTypeDefinition],
     variants: &[IdlEnumVariant],
-) -> FieldListProperties {
-    variants.iter().fold(
+) -> GeneratorResult<FieldListProperties> {
+    variants.iter().try_fold(
         FieldListProperties {
             can_copy: true,
             can_derive_default: true,
         },
         |acc, el| {
             let props = match &el.fields {
-                Some(EnumFields::Named(fields)) => get_field_list_properties(defs, fields),
-                Some(EnumFields::Tuple(fields)) => get_type_list_properties(defs, fields),
+                Some(EnumFields::Named(fields)) => get_field_list_properties(defs, fields)?,
+                Some(EnumFields::Tuple(fields)) => get_type_list_properties(defs, fields)?,
                 None => acc,
             };
-            FieldListProperties {
+            Ok(FieldListProperties {
                 can_copy: acc.can_copy && props.can_copy,
                 can_derive_default: acc.can_derive_default && props.can_derive_default,
-            }
+            })
         },
     )
 }
 
-pub fn get_type_properties(defs: &[IdlTypeDefinition], ty: &IdlType) -> FieldListProperties {
-    match ty {
+/// Properties of a type, looking up defined types in `defs`, which must include every type and
+/// account the type refers to.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{get_type_properties, IdlType};
+///
+/// assert!(get_type_properties(&[], &IdlType::Vec(Box::new(IdlType::U8))).unwrap().can_derive_default);
+/// let err = get_type_properties(&[], &IdlType::Defined("Mode".to_string())).unwrap_err();
+/// assert_eq!(err.message, "type Mode is not defined");
+/// ```
+pub fn get_type_properties(
+    defs: &[IdlTypeDefinition],
+    ty: &IdlType,
+) -> GeneratorResult<FieldListProperties> {
+    Ok(match ty {
         IdlType::Bool
         | IdlType::U8
         | IdlType::I8
@@ -99,38 +115,267 @@ pub fn get_type_properties(defs: &[IdlTypeDefinition], ty: &IdlType) -> FieldLis
             can_copy: false,
             can_derive_default: true,
         },
-        IdlType::Defined(inner) => {
-            let def = defs.iter().find(|def| def.name == *inner).unwrap();
-            match &def.ty {
-                IdlTypeDefinitionTy::Struct { fields } => get_field_list_properties(defs, fields),
-                IdlTypeDefinitionTy::Enum { variants } => {
-                    get_variant_list_properties(defs, variants)
-                }
-                IdlTypeDefinitionTy::Alias { value } => todo!(),
-            }
-        }
-        IdlType::Option(inner) => get_type_properties(defs, inner),
+        IdlType::Defined(inner) => match &find_typedef(defs, inner)?.ty {
+            IdlTypeDefinitionTy::Struct { fields } => get_field_list_properties(defs, fields)?,
+            IdlTypeDefinitionTy::Enum { variants } => get_variant_list_properties(defs, variants)?,
+            IdlTypeDefinitionTy::Alias { value } => get_type_properties(defs, value)?,
+        },
+        // Derives on generic types bound each type parameter, so the parameter itself does not
+        // restrict which traits may be derived.
+        IdlType::Generic(_) => FieldListProperties {
+            can_copy: true,
+            can_derive_default: true,
+        },
+        IdlType::Option(inner) => get_type_properties(defs, inner)?,
         IdlType::Array(inner, len) => {
-            let inner = get_type_properties(defs, inner);
+            let inner = get_type_properties(defs, inner)?;
             let can_derive_array_len = *len <= 32;
             FieldListProperties {
                 can_copy: inner.can_copy,
                 can_derive_default: can_derive_array_len && inner.can_derive_default,
             }
         }
-        IdlType::GenericLenArray(_, _) => todo!(),
-        IdlType::Generic(_) => todo!(),
-        IdlType::DefinedWithTypeArgs { name, args } => todo!(),
+        // `Default` is only implemented for arrays of at most 32 elements, so it cannot be derived
+        // for arrays of a generic length.
+        IdlType::GenericLenArray(inner, _) => FieldListProperties {
+            can_copy: get_type_properties(defs, inner)?.can_copy,
+            can_derive_default: false,
+        },
+        IdlType::DefinedWithTypeArgs { name, args } => {
+            let def = find_typedef(defs, name)?;
+            // A trait is implemented for a use of a generic type if it was derived for the
+            // generic type and holds for the substituted arguments.
+            let generic_props = get_type_properties(defs, &IdlType::Defined(name.clone()))?;
+            let substituted = substitute_typedef_generics(def, args);
+            let props = match &substituted {
+                IdlTypeDefinitionTy::Struct { fields } => get_field_list_properties(defs, fields)?,
+                IdlTypeDefinitionTy::Enum { variants } => {
+                    get_variant_list_properties(defs, variants)?
+                }
+                IdlTypeDefinitionTy::Alias { value } => get_type_properties(defs, value)?,
+            };
+            FieldListProperties {
+                can_copy: generic_props.can_copy && props.can_copy,
+                can_derive_default: generic_props.can_derive_default && props.can_derive_default,
+            }
+        }
+    })
+}
+
+fn find_typedef<'a>(
+    defs: &'a [IdlTypeDefinition],
+    name: &str,
+) -> GeneratorResult<&'a IdlTypeDefinition> {
+    defs.iter()
+        .find(|def| def.name == name)
+        .ok_or_else(|| GeneratorError::new(format!("type {} is not defined", name)))
+}
+
+/// Replaces the generic parameters of `def` with `args`, the arguments of a use of the type.
+pub fn substitute_typedef_generics(
+    def: &IdlTypeDefinition,
+    args: &[IdlDefinedTypeArg],
+) -> IdlTypeDefinitionTy {
+    let params: HashMap<&str, &IdlDefinedTypeArg> = def
+        .generics
+        .iter()
+        .flatten()
+        .map(|name| name.as_str())
+        .zip(args)
+        .collect();
+    let substitute_fields = |fields: &[IdlField]| {
+        fields
+            .iter()
+            .map(|field| IdlField {
+                ty: substitute_generics(&field.ty, &params),
+                ..field.clone()
+            })
+            .collect::<Vec<_>>()
+    };
+    match &def.ty {
+        IdlTypeDefinitionTy::Struct { fields } => IdlTypeDefinitionTy::Struct {
+            fields: substitute_fields(fields),
+        },
+        IdlTypeDefinitionTy::Enum { variants } => IdlTypeDefinitionTy::Enum {
+            variants: variants
+                .iter()
+                .map(|variant| IdlEnumVariant {
+                    name: variant.name.clone(),
+                    fields: variant.fields.as_ref().map(|fields| match fields {
+                        EnumFields::Named(fields) => EnumFields::Named(substitute_fields(fields)),
+                        EnumFields::Tuple(types) => EnumFields::Tuple(
+                            types
+                                .iter()
+                                .map(|ty| substitute_generics(ty, &params))
+                                .collect(),
+                        ),
+                    }),
+                })
+                .collect(),
+        },
+        IdlTypeDefinitionTy::Alias { value } => IdlTypeDefinitionTy::Alias {
+            value: substitute_generics(value, &params),
+        },
     }
 }
 
+/// Replaces generic parameters in `ty` with the arguments in `params`.
+fn substitute_generics(ty: &IdlType, params: &HashMap<&str, &IdlDefinedTypeArg>) -> IdlType {
+    let substitute_arg = |arg: &IdlDefinedTypeArg| match arg {
+        IdlDefinedTypeArg::Generic(name) | IdlDefinedTypeArg::Value(name) => {
+            match params.get(name.as_str()) {
+                Some(arg) => (*arg).clone(),
+                None => arg.clone(),
+            }
+        }
+        IdlDefinedTypeArg::Type(ty) => IdlDefinedTypeArg::Type(substitute_generics(ty, params)),
+    };
+    match ty {
+        IdlType::Generic(name) => match params.get(name.as_str()) {
+            Some(IdlDefinedTypeArg::Type(ty)) => ty.clone(),
+            Some(IdlDefinedTypeArg::Generic(name)) => IdlType::Generic(name.clone()),
+            _ => ty.clone(),
+        },
+        IdlType::GenericLenArray(inner, len) => {
+            let inner = Box::new(substitute_generics(inner, params));
+            match params.get(len.as_str()) {
+                Some(IdlDefinedTypeArg::Value(value)) => match value.parse() {
+                    Ok(len) => IdlType::Array(inner, len),
+                    Err(_) => IdlType::GenericLenArray(inner, value.clone()),
+                },
+                Some(IdlDefinedTypeArg::Generic(name)) => {
+                    IdlType::GenericLenArray(inner, name.clone())
+                }
+                _ => IdlType::GenericLenArray(inner, len.clone()),
+            }
+        }
+        IdlType::DefinedWithTypeArgs { name, args } => IdlType::DefinedWithTypeArgs {
+            name: name.clone(),
+            args: args.iter().map(substitute_arg).collect(),
+        },
+        IdlType::Option(inner) => IdlType::Option(Box::new(substitute_generics(inner, params))),
+        IdlType::Vec(inner) => IdlType::Vec(Box::new(substitute_generics(inner, params))),
+        IdlType::Array(inner, len) => {
+            IdlType::Array(Box::new(substitute_generics(inner, params)), *len)
+        }
+        _ => ty.clone(),
+    }
+}
+
+/// Returns true if the generic parameter `param` of `def` is used as an array length, which makes
+/// it a `const` parameter rather than a type parameter.
+fn is_const_generic(defs: &[IdlTypeDefinition], def: &IdlTypeDefinition, param: &str) -> bool {
+    is_const_generic_inner(defs, def, param, &mut HashSet::new())
+}
+
+fn is_const_generic_inner<'a>(
+    defs: &'a [IdlTypeDefinition],
+    def: &'a IdlTypeDefinition,
+    param: &'a str,
+    visited: &mut HashSet<(&'a str, &'a str)>,
+) -> bool {
+    // Recursive types are only inspected once.
+    if !visited.insert((def.name.as_str(), param)) {
+        return false;
+    }
+    let types: Vec<&IdlType> = match &def.ty {
+        IdlTypeDefinitionTy::Struct { fields } => fields.iter().map(|f| &f.ty).collect(),
+        IdlTypeDefinitionTy::Enum { variants } => variants
+            .iter()
+            .flat_map(|variant| match &variant.fields {
+                Some(EnumFields::Named(fields)) => fields.iter().map(|f| &f.ty).collect(),
+                Some(EnumFields::Tuple(types)) => types.iter().collect(),
+                None => vec![],
+            })
+            .collect(),
+        IdlTypeDefinitionTy::Alias { value } => vec![value],
+    };
+    types
+        .into_iter()
+        .any(|ty| is_const_generic_in_type(defs, ty, param, visited))
+}
+
+fn is_const_generic_in_type<'a>(
+    defs: &'a [IdlTypeDefinition],
+    ty: &'a IdlType,
+    param: &'a str,
+    visited: &mut HashSet<(&'a str, &'a str)>,
+) -> bool {
+    match ty {
+        IdlType::GenericLenArray(inner, len) => {
+            len == param || is_const_generic_in_type(defs, inner, param, visited)
+        }
+        IdlType::Option(inner) | IdlType::Vec(inner) | IdlType::Array(inner, _) => {
+            is_const_generic_in_type(defs, inner, param, visited)
+        }
+        IdlType::DefinedWithTypeArgs { name, args } => {
+            let def = defs.iter().find(|def| def.name == *name);
+            args.iter().enumerate().any(|(i, arg)| match arg {
+                // The parameter is forwarded to another type, so it is const if the parameter it
+                // is forwarded to is.
+                IdlDefinedTypeArg::Generic(arg) | IdlDefinedTypeArg::Value(arg) if arg == param => {
+                    match def.and_then(|def| Some((def, def.generics.as_ref()?.get(i)?))) {
+                        Some((def, generic)) => is_const_generic_inner(defs, def, generic, visited),
+                        None => false,
+                    }
+                }
+                IdlDefinedTypeArg::Type(ty) => is_const_generic_in_type(defs, ty, param, visited),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Generates the generic parameters of a user-defined type, e.g. `<T, const N: usize>`.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{generate_generics, parse_idl};
+///
+/// let loaded = parse_idl(
+///     r#"{
+///         "version": "0.1.0",
+///         "name": "ring",
+///         "instructions": [],
+///         "types": [{
+///             "name": "Ring",
+///             "generics": ["T", "N"],
+///             "type": {
+///                 "kind": "struct",
+///                 "fields": [{ "name": "items", "type": { "genericLenArray": [{ "generic": "T" }, "N"] } }]
+///             }
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+/// let generics = generate_generics(&loaded.idl.types, &loaded.idl.types[0]);
+/// assert_eq!(quote::quote!(#generics).to_string(), "< T , const N : usize >");
+/// ```
+pub fn generate_generics(defs: &[IdlTypeDefinition], def: &IdlTypeDefinition) -> Generics {
+    let params = def.generics.iter().flatten().map(|name| {
+        let ident = format_ident!("{}", name);
+        if is_const_generic(defs, def, name) {
+            quote! { const #ident: usize }
+        } else {
+            quote! { #ident }
+        }
+    });
+    parse_quote! { <#(#params),*> }
+}
+
 /// Generates struct fields from a list of [IdlField]s.
-pub fn generate_fields(fields: &[IdlField]) -> TokenStream {
+pub fn generate_fields(fields: &[IdlField], serde: bool) -> TokenStream {
     let fields_rendered = fields.iter().map(|arg| {
         let name = format_ident!("{}", arg.name.to_snake_case());
         let type_name = crate::ty_to_rust_type(&arg.ty);
         let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
+        let docs = crate::generate_docs(arg.docs.as_deref());
+        let serde_attrs = generate_serde_field_attrs(&name, &arg.name, &arg.ty, serde);
         quote! {
+            #docs
+            #serde_attrs
             pub #name: #stream
         }
     });
@@ -140,12 +385,14 @@ pub fn generate_fields(fields: &[IdlField]) -> TokenStream {
 }
 
 /// Generates event fields from a list of [IdlField]s.
-pub fn generate_event_fields(fields: &[IdlEventField]) -> TokenStream {
+pub fn generate_event_fields(fields: &[IdlEventField], serde: bool) -> TokenStream {
     let fields_rendered = fields.iter().map(|arg| {
         let name = format_ident!("{}", arg.name.to_snake_case());
         let type_name = crate::ty_to_rust_type(&arg.ty);
         let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
+        let serde_attrs = generate_serde_field_attrs(&name, &arg.name, &arg.ty, serde);
         quote! {
+            #serde_attrs
             pub #name: #stream
         }
     });
@@ -154,15 +401,110 @@ pub fn generate_event_fields(fields: &[IdlEventField]) -> TokenStream {
     }
 }
 
+/// Generates the serde derives of a type, through the serde re-exported by anchor-gen.
+///
+/// Type parameters are bounded explicitly, as serde does not infer the bounds of fields with a
+/// custom representation.
+pub fn generate_serde_derive(generics: &Generics, serde: bool) -> TokenStream {
+    if !serde {
+        return quote! {};
+    }
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect::<Vec<_>>();
+    let bound = if params.is_empty() {
+        quote! {}
+    } else {
+        let serialize = params
+            .iter()
+            .map(|param| format!("{}: anchor_gen::serde::Serialize", param))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let deserialize = params
+            .iter()
+            .map(|param| format!("{}: anchor_gen::serde::Deserialize<'de>", param))
+            .collect::<Vec<_>>()
+            .join(", ");
+        quote! {
+            #[serde(bound(serialize = #serialize, deserialize = #deserialize))]
+        }
+    };
+    quote! {
+        #[derive(anchor_gen::serde::Serialize, anchor_gen::serde::Deserialize)]
+        #[serde(crate = "anchor_gen::serde")]
+        #bound
+    }
+}
+
+/// Generates the serde attributes of a field: its name in the IDL, and the representation of
+/// its type if JSON cannot represent it faithfully. See [crate::serde_as].
+pub fn generate_serde_field_attrs(
+    name: &Ident,
+    idl_name: &str,
+    ty: &IdlType,
+    serde: bool,
+) -> TokenStream {
+    if !serde {
+        return quote! {};
+    }
+    let rename = if *name != idl_name {
+        quote! {
+            #[serde(rename = #idl_name)]
+        }
+    } else {
+        quote! {}
+    };
+    let with = serde_repr(ty).map(|repr| {
+        let serialize = format!("anchor_gen::serde_as::serialize::<{}, _, _>", repr);
+        let deserialize = format!("anchor_gen::serde_as::deserialize::<{}, _, _>", repr);
+        quote! {
+            #[serde(serialize_with = #serialize, deserialize_with = #deserialize)]
+        }
+    });
+    quote! {
+        #rename
+        #with
+    }
+}
+
+/// The [crate::serde_as] representation of a type, if it differs from its serde implementation:
+/// public keys and integers of more than 53 bits are strings, and arrays of more than 32
+/// elements, which serde does not support, are sequences.
+fn serde_repr(ty: &IdlType) -> Option<String> {
+    const PATH: &str = "anchor_gen::serde_as";
+    match ty {
+        IdlType::PublicKey | IdlType::U64 | IdlType::U128 | IdlType::I128 => {
+            Some(format!("{}::DisplayFromStr", PATH))
+        }
+        IdlType::Option(inner) => {
+            serde_repr(inner).map(|repr| format!("{}::OptionOf<{}>", PATH, repr))
+        }
+        IdlType::Vec(inner) => serde_repr(inner).map(|repr| format!("{}::VecOf<{}>", PATH, repr)),
+        IdlType::Array(inner, len) => match serde_repr(inner) {
+            Some(repr) => Some(format!("{}::ArrayOf<{}>", PATH, repr)),
+            None if *len > 32 => Some(format!("{}::ArrayOf<{}::Same>", PATH, PATH)),
+            None => None,
+        },
+        IdlType::GenericLenArray(inner, _) => {
+            let repr = serde_repr(inner).unwrap_or_else(|| format!("{}::Same", PATH));
+            Some(format!("{}::ArrayOf<{}>", PATH, repr))
+        }
+        _ => None,
+    }
+}
+
 /// Generates a struct.
 pub fn generate_struct(
     defs: &[IdlTypeDefinition],
     struct_name: &Ident,
+    generics: &Generics,
     fields: &[IdlField],
     opts: StructOpts,
-) -> TokenStream {
-    let fields_rendered = generate_fields(fields);
-    let props = get_field_list_properties(defs, fields);
+    serde: bool,
+) -> GeneratorResult<TokenStream> {
+    let fields_rendered = generate_fields(fields, serde);
+    let props = get_field_list_properties(defs, fields)?;
 
     let derive_default = if props.can_derive_default {
         quote! {
@@ -172,9 +514,11 @@ pub fn generate_struct(
         quote! {}
     };
     let derive_serializers = if opts.zero_copy {
+        // Fields keep the order of the IDL only with `repr(C)`, which the `Pod` derive of
+        // `zero_copy` also requires, so packed structs are `repr(C, packed)`.
         let repr = if opts.packed {
             quote! {
-                #[repr(packed)]
+                #[repr(C, packed)]
             }
         } else {
             quote! {
@@ -198,22 +542,26 @@ pub fn generate_struct(
             #derive_copy
         }
     };
+    let derive_serde = generate_serde_derive(generics, serde);
 
-    quote! {
+    Ok(quote! {
         #derive_serializers
         #[derive(Debug)]
         #derive_default
-        pub struct #struct_name {
+        #derive_serde
+        pub struct #struct_name #generics {
             #fields_rendered
         }
-    }
+    })
 }
 
 /// Generates an event.
-pub fn generate_event(event_name: &Ident, fields: &[IdlEventField]) -> TokenStream {
-    let fields_rendered = generate_event_fields(fields);
+pub fn generate_event(event_name: &Ident, fields: &[IdlEventField], serde: bool) -> TokenStream {
+    let fields_rendered = generate_event_fields(fields, serde);
+    let derive_serde = generate_serde_derive(&Generics::default(), serde);
     quote! {
         #[event]
+        #derive_serde
         pub struct #event_name {
             #fields_rendered
         }
@@ -224,10 +572,14 @@ pub fn generate_event(event_name: &Ident, fields: &[IdlEventField]) -> TokenStre
 pub fn generate_enum(
     defs: &[IdlTypeDefinition],
     enum_name: &Ident,
+    generics: &Generics,
     variants: &[IdlEnumVariant],
-) -> TokenStream {
-    let variant_idents = variants.iter().map(|v| format_ident!("{}", v.name));
-    let props = get_variant_list_properties(defs, variants);
+    serde: bool,
+) -> GeneratorResult<TokenStream> {
+    let variants_rendered = variants
+        .iter()
+        .map(|variant| generate_enum_variant(variant, serde));
+    let props = get_variant_list_properties(defs, variants)?;
 
     let derive_copy = if props.can_copy {
         quote! {
@@ -237,51 +589,190 @@ pub fn generate_enum(
         quote! {}
     };
 
-    let default_variant = format_ident!("{}", variants.first().unwrap().name);
+    // Variants are tagged explicitly, so that unit variants and those with fields have the same
+    // shape in JSON.
+    let derive_serde = if serde {
+        let derive = generate_serde_derive(generics, serde);
+        quote! {
+            #derive
+            #[serde(tag = "kind", content = "fields")]
+        }
+    } else {
+        quote! {}
+    };
+
+    let default_impl = generate_enum_default(defs, enum_name, generics, variants.first().unwrap())?;
 
-    quote! {
+    Ok(quote! {
         #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
         #derive_copy
-        pub enum #enum_name {
-            #(#variant_idents),*
+        #derive_serde
+        pub enum #enum_name #generics {
+            #(#variants_rendered),*
         }
 
-        impl Default for #enum_name {
-            fn default() -> Self {
-                Self::#default_variant
+        #default_impl
+    })
+}
+
+/// Generates a single enum variant, including any named or tuple fields it carries.
+pub fn generate_enum_variant(variant: &IdlEnumVariant, serde: bool) -> TokenStream {
+    let variant_name = format_ident!("{}", variant.name);
+    match &variant.fields {
+        Some(EnumFields::Named(fields)) => {
+            let fields_rendered = fields.iter().map(|field| {
+                let name = format_ident!("{}", field.name.to_snake_case());
+                let type_name = crate::ty_to_rust_type(&field.ty);
+                let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
+                let docs = crate::generate_docs(field.docs.as_deref());
+                let serde_attrs = generate_serde_field_attrs(&name, &field.name, &field.ty, serde);
+                quote! {
+                    #docs
+                    #serde_attrs
+                    #name: #stream
+                }
+            });
+            quote! {
+                #variant_name {
+                    #(#fields_rendered),*
+                }
+            }
+        }
+        Some(EnumFields::Tuple(fields)) => {
+            let fields_rendered = fields.iter().map(|ty| {
+                let type_name = crate::ty_to_rust_type(ty);
+                let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
+                // Tuple fields have no name, so only their representation is rendered.
+                let with = generate_serde_field_attrs(&variant_name, &variant.name, ty, serde);
+                quote! {
+                    #with
+                    #stream
+                }
+            });
+            quote! {
+                #variant_name(#(#fields_rendered),*)
             }
         }
+        None => quote! {
+            #variant_name
+        },
     }
 }
 
-/// Generates structs and enums.
-pub fn generate_typedefs(
-    typedefs: &[IdlTypeDefinition],
-    struct_opts: &BTreeMap<String, StructOpts>,
-) -> TokenStream {
-    let defined = typedefs.iter().map(|def| {
-        let struct_name = format_ident!("{}", def.name);
-        match &def.ty {
-            IdlTypeDefinitionTy::Struct { fields } => {
-                let opts = struct_opts.get(&def.name).copied().unwrap_or_default();
-                generate_struct(typedefs, &struct_name, fields, opts)
+/// Generates the [Default] implementation of an enum, which is its first variant.
+///
+/// If the first variant carries fields, each field is defaulted. No implementation is generated
+/// if any of those fields cannot be defaulted.
+fn generate_enum_default(
+    defs: &[IdlTypeDefinition],
+    enum_name: &Ident,
+    generics: &Generics,
+    variant: &IdlEnumVariant,
+) -> GeneratorResult<TokenStream> {
+    let variant_name = format_ident!("{}", variant.name);
+    let default_value = match &variant.fields {
+        Some(EnumFields::Named(fields)) => {
+            if !get_field_list_properties(defs, fields)?.can_derive_default {
+                return Ok(quote! {});
             }
-            IdlTypeDefinitionTy::Enum { variants } => {
-                generate_enum(typedefs, &struct_name, variants)
+            let fields_rendered = fields.iter().map(|field| {
+                let name = format_ident!("{}", field.name.to_snake_case());
+                quote! {
+                    #name: Default::default()
+                }
+            });
+            quote! {
+                Self::#variant_name {
+                    #(#fields_rendered),*
+                }
             }
-            IdlTypeDefinitionTy::Alias { value } => quote! {},
         }
-    });
+        Some(EnumFields::Tuple(fields)) => {
+            if !get_type_list_properties(defs, fields)?.can_derive_default {
+                return Ok(quote! {});
+            }
+            let fields_rendered = fields.iter().map(|_| quote! { Default::default() });
+            quote! {
+                Self::#variant_name(#(#fields_rendered),*)
+            }
+        }
+        None => quote! {
+            Self::#variant_name
+        },
+    };
+
+    let mut generics = generics.clone();
+    let type_params: Vec<Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    for param in type_params {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { #param: Default });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics Default for #enum_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #default_value
+            }
+        }
+    })
+}
+
+/// Generates a type alias.
+pub fn generate_alias(alias_name: &Ident, generics: &Generics, value: &IdlType) -> TokenStream {
+    let type_name = crate::ty_to_rust_type(value);
+    let stream: proc_macro2::TokenStream = type_name.parse().unwrap();
     quote! {
-        #(#defined)*
+        pub type #alias_name #generics = #stream;
     }
 }
 
+/// Generates structs, enums and type aliases.
+///
+/// The accounts of the IDL are given so that the derives of types which hold them are inferred.
+pub fn generate_typedefs(
+    typedefs: &[IdlTypeDefinition],
+    account_defs: &[IdlTypeDefinition],
+    struct_opts: &BTreeMap<String, StructOpts>,
+    serde: bool,
+) -> GeneratorResult<TokenStream> {
+    let defs = [typedefs, account_defs].concat();
+    let defined = typedefs
+        .iter()
+        .map(|def| {
+            let struct_name = format_ident!("{}", def.name);
+            let generics = generate_generics(typedefs, def);
+            let docs = crate::generate_docs(def.docs.as_deref());
+            let item = match &def.ty {
+                IdlTypeDefinitionTy::Struct { fields } => {
+                    let opts = struct_opts.get(&def.name).copied().unwrap_or_default();
+                    generate_struct(&defs, &struct_name, &generics, fields, opts, serde)?
+                }
+                IdlTypeDefinitionTy::Enum { variants } => {
+                    generate_enum(&defs, &struct_name, &generics, variants, serde)?
+                }
+                IdlTypeDefinitionTy::Alias { value } => {
+                    generate_alias(&struct_name, &generics, value)
+                }
+            };
+            Ok(quote! {
+                #docs
+                #item
+            })
+        })
+        .collect::<GeneratorResult<Vec<_>>>()?;
+    Ok(quote! {
+        #(#defined)*
+    })
+}
+
 /// Generates events.
-pub fn generate_events(events: &[IdlEvent]) -> TokenStream {
+pub fn generate_events(events: &[IdlEvent], serde: bool) -> TokenStream {
     let defined = events.iter().map(|evt| {
         let event_name = format_ident!("{}", evt.name);
-        generate_event(&event_name, &evt.fields)
+        generate_event(&event_name, &evt.fields, serde)
     });
     quote! {
         #(#defined)*
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use anchor_syn::idl::types::{
+    EnumFields, Idl, IdlDefinedTypeArg, IdlErrorCode, IdlField, IdlType, IdlTypeDefinition,
+    IdlTypeDefinitionTy,
+};
+
+use crate::{parse_constant, GeneratorError, GeneratorResult, IdlDiscriminators};
+
+/// Checks that every type referenced by a legacy IDL is defined, and that its constants and errors
+/// are valid. [parse_idl](crate::parse_idl) runs these checks on IDLs of either format.
+///
+/// Errors point to the JSON path of the offending node, e.g. `types[12].type.fields[3].type`.
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{validate_idl, Idl};
+///
+/// let idl: Idl = serde_json::from_str(
+///     r#"{
+///         "version": "0.1.0",
+///         "name": "counter",
+///         "instructions": [],
+///         "types": [{
+///             "name": "Counter",
+///             "type": { "kind": "struct", "fields": [{ "name": "mode", "type": { "defined": "Mode" } }] }
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+/// let err = validate_idl(&idl).unwrap_err();
+/// assert_eq!(err.path.as_deref(), Some("types[0].type.fields[0].type.defined"));
+/// ```
+pub fn validate_idl(idl: &Idl) -> GeneratorResult<()> {
+    let is_defined = |name: &str| {
+        idl.types
+            .iter()
+            .chain(idl.accounts.iter())
+            .any(|def| def.name == name)
+    };
+
+    for (i, def) in idl.types.iter().enumerate() {
+        validate_typedef(&is_defined, def, &format!("types[{}]", i))?;
+    }
+    for (i, def) in idl.accounts.iter().enumerate() {
+        validate_typedef(&is_defined, def, &format!("accounts[{}]", i))?;
+    }
+    for (i, ix) in idl.instructions.iter().enumerate() {
+        validate_fields(&is_defined, &ix.args, &format!("instructions[{}].args", i))?;
+        if let Some(returns) = &ix.returns {
+            validate_type(
+                &is_defined,
+                returns,
+                &format!("instructions[{}].returns", i),
+            )?;
+        }
+    }
+    for (i, event) in idl.events.iter().flatten().enumerate() {
+        for (j, field) in event.fields.iter().enumerate() {
+            validate_type(
+                &is_defined,
+                &field.ty,
+                &format!("events[{}].fields[{}].type", i, j),
+            )?;
+        }
+    }
+    for (i, constant) in idl.constants.iter().enumerate() {
+        validate_type(&is_defined, &constant.ty, &format!("constants[{}].type", i))?;
+        parse_constant(i, constant)?;
+    }
+    validate_errors(idl.errors.as_deref().unwrap_or_default())
+}
+
+/// Checks that the codes and names of errors are unique.
+pub fn validate_errors(errors: &[IdlErrorCode]) -> GeneratorResult<()> {
+    for (i, error) in errors.iter().enumerate() {
+        if errors[..i].iter().any(|other| other.code == error.code) {
+            return Err(GeneratorError::at(
+                format!("errors[{}].code", i),
+                format!("error code {} is used more than once", error.code),
+            ));
+        }
+        if errors[..i].iter().any(|other| other.name == error.name) {
+            return Err(GeneratorError::at(
+                format!("errors[{}].name", i),
+                format!("error {} is defined more than once", error.name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no discriminator of an account, instruction or event is a prefix of another of the
+/// same kind, in which case data starting with the longer one could be decoded as either.
+///
+/// Errors point to the later of the two, e.g. `accounts[3].discriminator`. The check is run by
+/// [parse_idl](crate::parse_idl).
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::parse_idl;
+///
+/// let err = parse_idl(
+///     r#"{
+///         "address": "Counter111111111111111111111111111111111111",
+///         "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+///         "instructions": [],
+///         "accounts": [
+///             { "name": "Counter", "discriminator": [1, 2] },
+///             { "name": "Config", "discriminator": [1, 2, 3] }
+///         ],
+///         "types": [
+///             { "name": "Counter", "type": { "kind": "struct", "fields": [] } },
+///             { "name": "Config", "type": { "kind": "struct", "fields": [] } }
+///         ]
+///     }"#,
+/// )
+/// .unwrap_err();
+/// assert_eq!(err.path.as_deref(), Some("accounts[1].discriminator"));
+/// ```
+pub fn validate_discriminators(
+    idl: &Idl,
+    discriminators: &IdlDiscriminators,
+) -> GeneratorResult<()> {
+    let accounts = idl.accounts.iter().map(|def| &def.name);
+    validate_discriminators_of("accounts", accounts, &discriminators.accounts, |name| {
+        discriminators.account(name)
+    })?;
+    let instructions = idl.instructions.iter().map(|ix| &ix.name);
+    validate_discriminators_of(
+        "instructions",
+        instructions,
+        &discriminators.instructions,
+        |name| discriminators.instruction(name),
+    )?;
+    let events = idl.events.iter().flatten().map(|event| &event.name);
+    validate_discriminators_of("events", events, &discriminators.events, |name| {
+        discriminators.event(name)
+    })
+}
+
+/// Checks the discriminators of the accounts, instructions or events named `names`. Errors point to
+/// the discriminator if it is recorded in the IDL, and otherwise to the name it is derived from.
+fn validate_discriminators_of<'a>(
+    kind: &str,
+    names: impl Iterator<Item = &'a String>,
+    recorded: &BTreeMap<String, Vec<u8>>,
+    discriminator: impl Fn(&str) -> Vec<u8>,
+) -> GeneratorResult<()> {
+    let mut seen: Vec<(&str, Vec<u8>)> = vec![];
+    for (i, name) in names.enumerate() {
+        let discrim = discriminator(name);
+        let path = if recorded.contains_key(name) {
+            format!("{}[{}].discriminator", kind, i)
+        } else {
+            format!("{}[{}].name", kind, i)
+        };
+        if discrim.is_empty() {
+            return Err(GeneratorError::at(
+                path,
+                format!("discriminator of {} is empty", name),
+            ));
+        }
+        if let Some((other, _)) = seen
+            .iter()
+            .find(|(_, other)| other.starts_with(&discrim) || discrim.starts_with(other))
+        {
+            return Err(GeneratorError::at(
+                path,
+                format!(
+                    "discriminators of {} and {} overlap, so their data cannot be told apart",
+                    other, name
+                ),
+            ));
+        }
+        seen.push((name, discrim));
+    }
+    Ok(())
+}
+
+fn validate_typedef(
+    is_defined: &impl Fn(&str) -> bool,
+    def: &IdlTypeDefinition,
+    path: &str,
+) -> GeneratorResult<()> {
+    match &def.ty {
+        IdlTypeDefinitionTy::Struct { fields } => {
+            validate_fields(is_defined, fields, &format!("{}.type.fields", path))
+        }
+        IdlTypeDefinitionTy::Enum { variants } => {
+            if variants.is_empty() {
+                return Err(GeneratorError::at(
+                    format!("{}.type.variants", path),
+                    format!("enum {} has no variants", def.name),
+                ));
+            }
+            for (i, variant) in variants.iter().enumerate() {
+                let fields_path = format!("{}.type.variants[{}].fields", path, i);
+                match &variant.fields {
+                    Some(EnumFields::Named(fields)) => {
+                        validate_fields(is_defined, fields, &fields_path)?
+                    }
+                    Some(EnumFields::Tuple(types)) => {
+                        for (j, ty) in types.iter().enumerate() {
+                            validate_type(is_defined, ty, &format!("{}[{}]", fields_path, j))?;
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Ok(())
+        }
+        IdlTypeDefinitionTy::Alias { value } => {
+            validate_type(is_defined, value, &format!("{}.type.value", path))
+        }
+    }
+}
+
+fn validate_fields(
+    is_defined: &impl Fn(&str) -> bool,
+    fields: &[IdlField],
+    path: &str,
+) -> GeneratorResult<()> {
+    for (i, field) in fields.iter().enumerate() {
+        validate_type(is_defined, &field.ty, &format!("{}[{}].type", path, i))?;
+    }
+    Ok(())
+}
+
+fn validate_type(
+    is_defined: &impl Fn(&str) -> bool,
+    ty: &IdlType,
+    path: &str,
+) -> GeneratorResult<()> {
+    match ty {
+        IdlType::Defined(name) if !is_defined(name) => Err(GeneratorError::at(
+            format!("{}.defined", path),
+            format!("type {} is not defined", name),
+        )),
+        IdlType::DefinedWithTypeArgs { name, .. } if !is_defined(name) => Err(GeneratorError::at(
+            format!("{}.definedWithTypeArgs.name", path),
+            format!("type {} is not defined", name),
+        )),
+        IdlType::DefinedWithTypeArgs { args, .. } => {
+            for (i, arg) in args.iter().enumerate() {
+                if let IdlDefinedTypeArg::Type(ty) = arg {
+                    validate_type(
+                        is_defined,
+                        ty,
+                        &format!("{}.definedWithTypeArgs.args[{}].type", path, i),
+                    )?;
+                }
+            }
+            Ok(())
+        }
+        IdlType::Option(inner) => validate_type(is_defined, inner, &format!("{}.option", path)),
+        IdlType::Vec(inner) => validate_type(is_defined, inner, &format!("{}.vec", path)),
+        IdlType::Array(inner, _) => validate_type(is_defined, inner, &format!("{}.array[0]", path)),
+        IdlType::GenericLenArray(inner, _) => {
+            validate_type(is_defined, inner, &format!("{}.genericLenArray[0]", path))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,620 @@
+use std::{collections::BTreeMap, io, mem};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_syn::idl::types::{
+    EnumFields, Idl, IdlEvent, IdlField, IdlInstruction, IdlType, IdlTypeDefinition,
+    IdlTypeDefinitionTy,
+};
+
+use crate::{substitute_typedef_generics, DecodeError, IdlDiscriminators, LoadedIdl, StructOpts};
+
+/// Maximum nesting of the values decoded by [IdlDecoder], which bounds the recursion of
+/// recursive types.
+const MAX_DEPTH: usize = 64;
+
+/// A value decoded at runtime from its [IdlType].
+#[derive(Clone, Debug, PartialEq)]
+pub enum IdlValue {
+    Bool(bool),
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    F32(f32),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    U128(u128),
+    I128(i128),
+    /// Little endian bytes of an unsigned 256 bit integer.
+    U256([u8; 32]),
+    /// Little endian bytes of a signed 256 bit integer.
+    I256([u8; 32]),
+    Bytes(Vec<u8>),
+    String(String),
+    PublicKey(Pubkey),
+    Option(Option<Box<IdlValue>>),
+    Vec(Vec<IdlValue>),
+    Array(Vec<IdlValue>),
+    /// Fields of a struct, in the order of the IDL.
+    Struct(Vec<(String, IdlValue)>),
+    /// A variant of an enum.
+    Enum {
+        variant: String,
+        fields: Option<IdlValueFields>,
+    },
+}
+
+/// Fields of an enum variant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IdlValueFields {
+    Named(Vec<(String, IdlValue)>),
+    Tuple(Vec<IdlValue>),
+}
+
+/// An account, instruction or event decoded by [IdlDecoder].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedValue {
+    /// Name of the account, instruction or event in the IDL.
+    pub name: String,
+    /// The fields of the account or event, or the arguments of the instruction.
+    pub value: IdlValue,
+}
+
+/// Decodes accounts, instructions and events of a program whose IDL is only known at runtime.
+///
+/// The discriminator is resolved as in the `derive_*_type!` macros, then the data is walked
+/// following the [IdlType]s of the IDL. Like the generated types, accounts must be decoded
+/// entirely, whereas bytes following instructions and events are ignored.
+///
+/// Accounts are decoded with Borsh, except zero copy accounts, which are laid out as their
+/// `repr(C)` or `repr(C, packed)` struct: fields are aligned as in the generated types, and, as
+/// with Anchor's `AccountLoader`, bytes following the account are ignored. See
+/// [IdlDecoder::with_struct_opts].
+///
+/// # Examples
+///
+/// ```
+/// use anchor_idl::{parse_idl, DecodedValue, IdlDecoder, IdlValue, IdlValueFields};
+///
+/// let loaded = parse_idl(
+///     r#"{
+///         "version": "0.1.0",
+///         "name": "counter",
+///         "instructions": [],
+///         "accounts": [{
+///             "name": "Counter",
+///             "type": {
+///                 "kind": "struct",
+///                 "fields": [
+///                     { "name": "count", "type": "u64" },
+///                     { "name": "mode", "type": { "option": { "defined": "Mode" } } }
+///                 ]
+///             }
+///         }],
+///         "types": [{
+///             "name": "Mode",
+///             "type": {
+///                 "kind": "enum",
+///                 "variants": [{ "name": "Off" }, { "name": "Step", "fields": ["u8"] }]
+///             }
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+/// let decoder = IdlDecoder::from(&loaded);
+///
+/// let mut data = anchor_idl::account_discriminator("Counter").to_vec();
+/// data.extend_from_slice(&7u64.to_le_bytes());
+/// data.extend_from_slice(&[1, 1, 3]);
+///
+/// assert_eq!(
+///     decoder.decode_account(&data).unwrap(),
+///     DecodedValue {
+///         name: "Counter".to_string(),
+///         value: IdlValue::Struct(vec![
+///             ("count".to_string(), IdlValue::U64(7)),
+///             (
+///                 "mode".to_string(),
+///                 IdlValue::Option(Some(Box::new(IdlValue::Enum {
+///                     variant: "Step".to_string(),
+///                     fields: Some(IdlValueFields::Tuple(vec![IdlValue::U8(3)])),
+///                 }))),
+///             ),
+///         ]),
+///     }
+/// );
+/// assert!(decoder.decode_account(&data[..10]).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct IdlDecoder<'a> {
+    idl: &'a Idl,
+    struct_opts: BTreeMap<String, StructOpts>,
+    accounts: Vec<(&'a IdlTypeDefinition, Vec<u8>)>,
+    instructions: Vec<(&'a IdlInstruction, Vec<u8>)>,
+    events: Vec<(&'a IdlEvent, Vec<u8>)>,
+}
+
+impl<'a> From<&'a LoadedIdl> for IdlDecoder<'a> {
+    fn from(loaded: &'a LoadedIdl) -> Self {
+        IdlDecoder::new(&loaded.idl, &loaded.discriminators).with_struct_opts(&loaded.struct_opts)
+    }
+}
+
+impl<'a> IdlDecoder<'a> {
+    /// Creates a decoder for `idl`. Discriminators missing from `discriminators` are derived from
+    /// the names of the accounts, instructions and events.
+    pub fn new(idl: &'a Idl, discriminators: &IdlDiscriminators) -> Self {
+        IdlDecoder {
+            idl,
+            struct_opts: BTreeMap::new(),
+            accounts: idl
+                .accounts
+                .iter()
+                .map(|def| (def, discriminators.account(&def.name)))
+                .collect(),
+            instructions: idl
+                .instructions
+                .iter()
+                .map(|ix| (ix, discriminators.instruction(&ix.name)))
+                .collect(),
+            events: idl
+                .events
+                .iter()
+                .flatten()
+                .map(|event| (event, discriminators.event(&event.name)))
+                .collect(),
+        }
+    }
+
+    /// Adds the options of structs, such as those recorded in the IDL or given to
+    /// `generate_cpi_crate!`, so that zero copy accounts are decoded with their C layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use anchor_idl::{parse_idl, IdlDecoder, IdlValue, StructOpts};
+    ///
+    /// let loaded = parse_idl(
+    ///     r#"{
+    ///         "version": "0.1.0",
+    ///         "name": "ticks",
+    ///         "instructions": [],
+    ///         "accounts": [{
+    ///             "name": "Tick",
+    ///             "type": {
+    ///                 "kind": "struct",
+    ///                 "fields": [{ "name": "index", "type": "u8" }, { "name": "liquidity", "type": "u32" }]
+    ///             }
+    ///         }]
+    ///     }"#,
+    /// )
+    /// .unwrap();
+    /// let zero_copy = StructOpts {
+    ///     zero_copy: true,
+    ///     packed: false,
+    /// };
+    /// let decoder = IdlDecoder::from(&loaded)
+    ///     .with_struct_opts(&BTreeMap::from([("Tick".to_string(), zero_copy)]));
+    ///
+    /// // `liquidity` is aligned to 4 bytes, after 3 bytes of padding.
+    /// let mut data = anchor_idl::account_discriminator("Tick").to_vec();
+    /// data.extend_from_slice(&[7, 0, 0, 0]);
+    /// data.extend_from_slice(&9u32.to_le_bytes());
+    /// assert_eq!(
+    ///     decoder.decode_account(&data).unwrap().value,
+    ///     IdlValue::Struct(vec![
+    ///         ("index".to_string(), IdlValue::U8(7)),
+    ///         ("liquidity".to_string(), IdlValue::U32(9)),
+    ///     ])
+    /// );
+    /// ```
+    pub fn with_struct_opts(mut self, struct_opts: &BTreeMap<String, StructOpts>) -> Self {
+        self.struct_opts
+            .extend(struct_opts.iter().map(|(name, opts)| (name.clone(), *opts)));
+        self
+    }
+
+    /// Decodes the data of an account.
+    pub fn decode_account(&self, data: &[u8]) -> Result<DecodedValue, DecodeError> {
+        let (def, mut rest) = find_discriminator(&self.accounts, data)?;
+        if self.opts(&def.name).zero_copy {
+            let len = rest.len();
+            return Ok(DecodedValue {
+                name: def.name.clone(),
+                value: self.decode_c_typedef(&def.name, &def.ty, &mut rest, len, 0)?,
+            });
+        }
+        let value = self.decode_typedef(&def.ty, &mut rest, 0)?;
+        if !rest.is_empty() {
+            return Err(DecodeError::TrailingBytes {
+                remaining: rest.len(),
+            });
+        }
+        Ok(DecodedValue {
+            name: def.name.clone(),
+            value,
+        })
+    }
+
+    /// Decodes the data of an instruction into a struct of its arguments.
+    pub fn decode_instruction(&self, data: &[u8]) -> Result<DecodedValue, DecodeError> {
+        let (ix, mut rest) = find_discriminator(&self.instructions, data)?;
+        Ok(DecodedValue {
+            name: ix.name.clone(),
+            value: self.decode_fields(&ix.args, &mut rest, 0)?,
+        })
+    }
+
+    /// Decodes the data of an event.
+    pub fn decode_event(&self, data: &[u8]) -> Result<DecodedValue, DecodeError> {
+        let (event, mut rest) = find_discriminator(&self.events, data)?;
+        let fields = event
+            .fields
+            .iter()
+            .map(|field| {
+                let value = self.decode_type(&field.ty, &mut rest, 1)?;
+                Ok((field.name.clone(), value))
+            })
+            .collect::<Result<_, DecodeError>>()?;
+        Ok(DecodedValue {
+            name: event.name.clone(),
+            value: IdlValue::Struct(fields),
+        })
+    }
+
+    /// Decodes a value of type `ty` from the start of `data`, advancing it past the value.
+    ///
+    /// `depth` is the nesting of the value, which is 0 for a top-level value.
+    pub fn decode_type(
+        &self,
+        ty: &IdlType,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<IdlValue, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("values are nested too deeply"));
+        }
+        Ok(match ty {
+            IdlType::Bool => match take::<1>(data)? {
+                [0] => IdlValue::Bool(false),
+                [1] => IdlValue::Bool(true),
+                _ => return Err(invalid_data("invalid bool")),
+            },
+            IdlType::U8 => IdlValue::U8(u8::from_le_bytes(take(data)?)),
+            IdlType::I8 => IdlValue::I8(i8::from_le_bytes(take(data)?)),
+            IdlType::U16 => IdlValue::U16(u16::from_le_bytes(take(data)?)),
+            IdlType::I16 => IdlValue::I16(i16::from_le_bytes(take(data)?)),
+            IdlType::U32 => IdlValue::U32(u32::from_le_bytes(take(data)?)),
+            IdlType::I32 => IdlValue::I32(i32::from_le_bytes(take(data)?)),
+            IdlType::U64 => IdlValue::U64(u64::from_le_bytes(take(data)?)),
+            IdlType::I64 => IdlValue::I64(i64::from_le_bytes(take(data)?)),
+            IdlType::U128 => IdlValue::U128(u128::from_le_bytes(take(data)?)),
+            IdlType::I128 => IdlValue::I128(i128::from_le_bytes(take(data)?)),
+            IdlType::U256 => IdlValue::U256(take(data)?),
+            IdlType::I256 => IdlValue::I256(take(data)?),
+            // Borsh rejects NaN, as it has no canonical representation.
+            IdlType::F32 => match f32::from_le_bytes(take(data)?) {
+                value if value.is_nan() => return Err(invalid_data("NaN is not allowed")),
+                value => IdlValue::F32(value),
+            },
+            IdlType::F64 => match f64::from_le_bytes(take(data)?) {
+                value if value.is_nan() => return Err(invalid_data("NaN is not allowed")),
+                value => IdlValue::F64(value),
+            },
+            IdlType::Bytes => IdlValue::Bytes(take_len_prefixed(data)?.to_vec()),
+            IdlType::String => match std::str::from_utf8(take_len_prefixed(data)?) {
+                Ok(value) => IdlValue::String(value.to_string()),
+                Err(_) => return Err(invalid_data("invalid UTF-8 string")),
+            },
+            IdlType::PublicKey => IdlValue::PublicKey(Pubkey::new_from_array(take(data)?)),
+            IdlType::Option(inner) => match take::<1>(data)? {
+                [0] => IdlValue::Option(None),
+                [1] => {
+                    IdlValue::Option(Some(Box::new(self.decode_type(inner, data, depth + 1)?)))
+                }
+                _ => return Err(invalid_data("invalid option tag")),
+            },
+            IdlType::Vec(inner) => {
+                let len = u32::from_le_bytes(take(data)?) as usize;
+                IdlValue::Vec(self.decode_elements(inner, len, data, depth)?)
+            }
+            IdlType::Array(inner, len) => {
+                IdlValue::Array(self.decode_elements(inner, *len, data, depth)?)
+            }
+            IdlType::Defined(name) => {
+                let def = self.find_typedef(name)?;
+                self.decode_typedef(&def.ty, data, depth)?
+            }
+            IdlType::DefinedWithTypeArgs { name, args } => {
+                let def = self.find_typedef(name)?;
+                self.decode_typedef(&substitute_typedef_generics(def, args), data, depth)?
+            }
+            // Generic parameters are substituted when their type is decoded, so any left are not
+            // bound by the IDL.
+            IdlType::Generic(name) | IdlType::GenericLenArray(_, name) => {
+                return Err(DecodeError::UnknownName(name.clone()))
+            }
+        })
+    }
+
+    fn decode_typedef(
+        &self,
+        ty: &IdlTypeDefinitionTy,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<IdlValue, DecodeError> {
+        match ty {
+            IdlTypeDefinitionTy::Struct { fields } => self.decode_fields(fields, data, depth),
+            IdlTypeDefinitionTy::Enum { variants } => {
+                let [index] = take::<1>(data)?;
+                let variant = variants
+                    .get(index as usize)
+                    .ok_or_else(|| invalid_data("invalid enum variant"))?;
+                let fields = match &variant.fields {
+                    None => None,
+                    Some(EnumFields::Named(fields)) => Some(IdlValueFields::Named(
+                        self.decode_named_fields(fields, data, depth)?,
+                    )),
+                    Some(EnumFields::Tuple(types)) => Some(IdlValueFields::Tuple(
+                        types
+                            .iter()
+                            .map(|ty| self.decode_type(ty, data, depth + 1))
+                            .collect::<Result<_, _>>()?,
+                    )),
+                };
+                Ok(IdlValue::Enum {
+                    variant: variant.name.clone(),
+                    fields,
+                })
+            }
+            IdlTypeDefinitionTy::Alias { value } => self.decode_type(value, data, depth),
+        }
+    }
+
+    fn decode_fields(
+        &self,
+        fields: &[IdlField],
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<IdlValue, DecodeError> {
+        Ok(IdlValue::Struct(
+            self.decode_named_fields(fields, data, depth)?,
+        ))
+    }
+
+    fn decode_named_fields(
+        &self,
+        fields: &[IdlField],
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<Vec<(String, IdlValue)>, DecodeError> {
+        fields
+            .iter()
+            .map(|field| {
+                let value = self.decode_type(&field.ty, data, depth + 1)?;
+                Ok((field.name.clone(), value))
+            })
+            .collect()
+    }
+
+    fn decode_elements(
+        &self,
+        ty: &IdlType,
+        len: usize,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<Vec<IdlValue>, DecodeError> {
+        // Every element takes at least a byte unless it is empty, so the length of the data bounds
+        // the allocation.
+        let mut elements = Vec::with_capacity(len.min(data.len()));
+        for _ in 0..len {
+            let remaining = data.len();
+            elements.push(self.decode_type(ty, data, depth + 1)?);
+            if data.len() == remaining && len > 1 {
+                return Err(invalid_data("elements of zero size are not supported"));
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Decodes a value of type `ty` laid out as in a `repr(C)` struct. Offsets are counted from
+    /// the start of the account, whose data following the discriminator is `len` bytes long.
+    ///
+    /// The value is aligned unless it is a field of a packed struct.
+    fn decode_c_type(
+        &self,
+        ty: &IdlType,
+        data: &mut &[u8],
+        len: usize,
+        aligned: bool,
+        depth: usize,
+    ) -> Result<IdlValue, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("values are nested too deeply"));
+        }
+        if aligned {
+            skip_padding(data, len, self.c_align(ty, depth)?)?;
+        }
+        match ty {
+            IdlType::Array(inner, count) => Ok(IdlValue::Array(
+                (0..*count)
+                    .map(|_| self.decode_c_type(inner, data, len, aligned, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            )),
+            IdlType::Defined(name) => {
+                let def = self.find_typedef(name)?;
+                self.decode_c_typedef(name, &def.ty, data, len, depth)
+            }
+            IdlType::Option(_)
+            | IdlType::Vec(_)
+            | IdlType::String
+            | IdlType::Bytes
+            | IdlType::DefinedWithTypeArgs { .. }
+            | IdlType::Generic(_)
+            | IdlType::GenericLenArray(..) => Err(not_zero_copy(ty)),
+            _ => self.decode_type(ty, data, depth),
+        }
+    }
+
+    fn decode_c_typedef(
+        &self,
+        name: &str,
+        ty: &IdlTypeDefinitionTy,
+        data: &mut &[u8],
+        len: usize,
+        depth: usize,
+    ) -> Result<IdlValue, DecodeError> {
+        match ty {
+            IdlTypeDefinitionTy::Struct { fields } => {
+                let packed = self.opts(name).packed;
+                let fields = fields
+                    .iter()
+                    .map(|field| {
+                        let value = self.decode_c_type(&field.ty, data, len, !packed, depth + 1)?;
+                        Ok((field.name.clone(), value))
+                    })
+                    .collect::<Result<_, DecodeError>>()?;
+                // Structs are padded to a multiple of their alignment.
+                if !packed {
+                    skip_padding(data, len, self.c_typedef_align(name, ty, depth)?)?;
+                }
+                Ok(IdlValue::Struct(fields))
+            }
+            // Enums without fields are laid out as their index, as in Anchor programs.
+            IdlTypeDefinitionTy::Enum { variants }
+                if variants.iter().all(|variant| variant.fields.is_none()) =>
+            {
+                self.decode_typedef(ty, data, depth)
+            }
+            IdlTypeDefinitionTy::Enum { .. } => Err(not_zero_copy(&IdlType::Defined(name.into()))),
+            IdlTypeDefinitionTy::Alias { value } => {
+                self.decode_c_type(value, data, len, true, depth)
+            }
+        }
+    }
+
+    /// Alignment of a type in a `repr(C)` struct, which is that of the generated Rust type.
+    fn c_align(&self, ty: &IdlType, depth: usize) -> Result<usize, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("values are nested too deeply"));
+        }
+        Ok(match ty {
+            IdlType::Bool
+            | IdlType::U8
+            | IdlType::I8
+            | IdlType::U256
+            | IdlType::I256
+            | IdlType::PublicKey => 1,
+            IdlType::U16 | IdlType::I16 => mem::align_of::<u16>(),
+            IdlType::U32 | IdlType::I32 => mem::align_of::<u32>(),
+            IdlType::F32 => mem::align_of::<f32>(),
+            IdlType::U64 | IdlType::I64 => mem::align_of::<u64>(),
+            IdlType::F64 => mem::align_of::<f64>(),
+            IdlType::U128 | IdlType::I128 => mem::align_of::<u128>(),
+            IdlType::Array(inner, _) => self.c_align(inner, depth + 1)?,
+            IdlType::Defined(name) => {
+                let def = self.find_typedef(name)?;
+                self.c_typedef_align(name, &def.ty, depth + 1)?
+            }
+            _ => return Err(not_zero_copy(ty)),
+        })
+    }
+
+    fn c_typedef_align(
+        &self,
+        name: &str,
+        ty: &IdlTypeDefinitionTy,
+        depth: usize,
+    ) -> Result<usize, DecodeError> {
+        match ty {
+            IdlTypeDefinitionTy::Struct { .. } if self.opts(name).packed => Ok(1),
+            IdlTypeDefinitionTy::Struct { fields } => fields.iter().try_fold(1, |align, field| {
+                Ok(align.max(self.c_align(&field.ty, depth + 1)?))
+            }),
+            IdlTypeDefinitionTy::Enum { .. } => Ok(1),
+            IdlTypeDefinitionTy::Alias { value } => self.c_align(value, depth + 1),
+        }
+    }
+
+    fn opts(&self, name: &str) -> StructOpts {
+        self.struct_opts.get(name).copied().unwrap_or_default()
+    }
+
+    fn find_typedef(&self, name: &str) -> Result<&'a IdlTypeDefinition, DecodeError> {
+        self.idl
+            .types
+            .iter()
+            .chain(&self.idl.accounts)
+            .find(|def| def.name == name)
+            .ok_or_else(|| DecodeError::UnknownName(name.to_string()))
+    }
+}
+
+/// Finds the item whose discriminator prefixes `data`, returning it with the data following the
+/// discriminator.
+fn find_discriminator<'a, 'd, T>(
+    items: &'a [(T, Vec<u8>)],
+    data: &'d [u8],
+) -> Result<(&'a T, &'d [u8]), DecodeError> {
+    items
+        .iter()
+        .find_map(|(item, discrim)| Some((item, data.strip_prefix(&discrim[..])?)))
+        .ok_or_else(|| {
+            let shortest = items.iter().map(|(_, d)| d.len()).min().unwrap_or(0);
+            let longest = items.iter().map(|(_, d)| d.len()).max().unwrap_or(0);
+            DecodeError::__unmatched(data, shortest, longest)
+        })
+}
+
+fn take<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    if data.len() < N {
+        return Err(unexpected_eof());
+    }
+    let (bytes, rest) = data.split_at(N);
+    *data = rest;
+    Ok(bytes.try_into().unwrap())
+}
+
+fn take_len_prefixed<'d>(data: &mut &'d [u8]) -> Result<&'d [u8], DecodeError> {
+    let len = u32::from_le_bytes(take(data)?) as usize;
+    if data.len() < len {
+        return Err(unexpected_eof());
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+/// Skips the padding which aligns the data at `offset` of `len` bytes to `align` bytes.
+fn skip_padding(data: &mut &[u8], len: usize, align: usize) -> Result<(), DecodeError> {
+    let offset = len - data.len();
+    let padding = (align - offset % align) % align;
+    if data.len() < padding {
+        return Err(unexpected_eof());
+    }
+    *data = &data[padding..];
+    Ok(())
+}
+
+fn not_zero_copy(ty: &IdlType) -> DecodeError {
+    invalid_data(&format!(
+        "{} cannot be laid out in a zero copy account",
+        crate::ty_to_rust_type(ty)
+    ))
+}
+
+fn unexpected_eof() -> DecodeError {
+    DecodeError::Borsh(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "unexpected end of data",
+    ))
+}
+
+fn invalid_data(message: &str) -> DecodeError {
+    DecodeError::Borsh(io::Error::new(io::ErrorKind::InvalidData, message))
+}
@@ -0,0 +1,104 @@
+//! Compares decoding Drift accounts with the `AccountType` emitted by `generate_cpi_crate!`
+//! against the `Decode` implementation `derive_account_type!` had before discriminators were
+//! emitted as constants, which hashed the name of every variant until one matched.
+//!
+//! Run with `cargo bench -p drift`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use anchor_gen::prelude::*;
+use anchor_lang::AnchorDeserialize;
+use drift::{
+    AccountType, PerpMarket, PhoenixV1FulfillmentConfig, PrelaunchOracle,
+    ProtocolIfSharesTransferConfig, ReferrerName, SerumV3FulfillmentConfig, SpotMarket, State,
+    User, UserStats,
+};
+
+const ITERATIONS: u32 = 100_000;
+
+/// `derive_account_type!` as of the baseline, with only its `Decode` implementation, which is
+/// inherent here. Lints of the original are allowed rather than fixed.
+macro_rules! baseline_account_type {
+    ($vis:vis enum $ident:ident {
+        $($variant:ident ($account_type:ty)),*$(,)?
+    }) => {
+        #[allow(dead_code, clippy::large_enum_variant)]
+        #[derive(Clone)]
+        $vis enum $ident {
+            $($variant($account_type),)*
+        }
+
+        impl $ident {
+          #[allow(unused_variables, non_snake_case, clippy::io_other_error)]
+          fn decode(data: &[u8]) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+            let discrim: &[u8; 8] = data[..8].try_into().map_err(|e| {
+              Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Instruction data is not 8 bytes or more".to_string()))
+            })?;
+            match discrim {
+              $(
+                $variant if discrim == &anchor_idl::account_discriminator(&anchor_idl::ident_name::<$account_type>()) => {
+                    let acct = <$account_type>::try_from_slice(&data[8..])?;
+                    Ok(Self::$variant(acct.clone()))
+                },
+              )*
+              _ => Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "Invalid account discriminator".to_string())))
+            }
+          }
+        }
+    };
+}
+
+// The variants of `AccountType`, in the same order, except the zero copy `InsuranceFundStake`,
+// which the baseline could not decode.
+baseline_account_type!(
+    enum BaselineAccountType {
+        PhoenixV1FulfillmentConfig(PhoenixV1FulfillmentConfig),
+        SerumV3FulfillmentConfig(SerumV3FulfillmentConfig),
+        ProtocolIfSharesTransferConfig(ProtocolIfSharesTransferConfig),
+        PrelaunchOracle(PrelaunchOracle),
+        PerpMarket(PerpMarket),
+        SpotMarket(SpotMarket),
+        State(State),
+        User(User),
+        UserStats(UserStats),
+        ReferrerName(ReferrerName),
+    }
+);
+
+fn bench(name: &str, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!("{:<16} {:>10.1?} per decode", name, elapsed / ITERATIONS);
+    elapsed
+}
+
+fn main() {
+    // `ReferrerName` is the last variant, so the baseline checks every other discriminator first.
+    let mut data = AccountType::name_to_discrim("ReferrerName").unwrap();
+    data.extend_from_slice(&[0; 128]);
+    assert!(matches!(
+        AccountType::decode(&data),
+        Ok(AccountType::ReferrerName(_))
+    ));
+    assert!(matches!(
+        BaselineAccountType::decode(&data),
+        Ok(BaselineAccountType::ReferrerName(_))
+    ));
+
+    let baseline = bench("baseline", || {
+        let _ = black_box(BaselineAccountType::decode(black_box(&data)));
+    });
+    let constant = bench("constant", || {
+        let _ = black_box(AccountType::decode(black_box(&data)));
+    });
+    println!(
+        "constant discriminators are {:.1}x faster",
+        baseline.as_secs_f64() / constant.as_secs_f64()
+    );
+}
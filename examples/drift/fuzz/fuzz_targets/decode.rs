@@ -0,0 +1,19 @@
+//! Decodes arbitrary data as Drift accounts, instructions and events, which must never panic.
+//!
+//! Run with `cargo +nightly fuzz run decode` from `examples/drift`.
+
+#![no_main]
+
+use anchor_gen::prelude::*;
+use drift::{AccountType, EventType, InstructionType};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = AccountType::decode(data);
+    let _ = InstructionType::decode(data);
+    let _ = EventType::decode(data);
+
+    let _ = AccountType::discrim_to_name(data);
+    let _ = InstructionType::discrim_to_name(data);
+    let _ = EventType::discrim_to_name(data);
+});
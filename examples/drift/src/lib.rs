@@ -2,7 +2,7 @@
 
 use anchor_gen::prelude::*;
 
-generate_cpi_crate!("idl.json");
+generate_cpi_crate!("idl.json", serde, zero_copy(InsuranceFundStake));
 declare_id!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
 
 #[test]
@@ -26,8 +26,10 @@ fn accounts() -> anyhow::Result<()> {
 
 #[test]
 fn instructions() -> anyhow::Result<()> {
+    use solana_sdk::bs58;
     use solana_transaction_status::EncodedTransaction;
     use solana_transaction_status::UiTransactionEncoding;
+    use solana_transaction_status::{UiInstruction, UiMessage, UiParsedInstruction};
     use std::str::FromStr;
 
     let rpc = solana_client::rpc_client::RpcClient::new(
@@ -55,30 +57,24 @@ fn instructions() -> anyhow::Result<()> {
                                     bs58::decode(ui_decoded_ix.data.clone()).into_vec()?;
                                 // only match instruction if it belongs to the IDL that generated this crate (the Drift program)
                                 if data.len() >= 8 && ui_decoded_ix.program_id == id().to_string() {
-                                    if let Ok(discrim) = data[..8].try_into() {
-                                        let ix =
-                                            InstructionType::decode(&data[..]).map_err(|e| {
-                                                anyhow::anyhow!(
-                                                    "Failed to decode instruction: {:?}",
-                                                    e
-                                                )
-                                            })?;
-                                        let name =
-                                            InstructionType::discrim_to_name(discrim).unwrap();
-                                        match ix {
-                                            InstructionType::PlacePerpOrder(ix) => {
-                                                println!("{}, {:#?}", name, ix._params);
-                                            }
-                                            InstructionType::PlaceAndTakePerpOrder(ix) => {
-                                                println!("{}, {:#?}", name, ix._params);
-                                            }
-                                            InstructionType::PlaceOrders(ix) => {
-                                                for params in ix._params {
-                                                    println!("{}, {:#?}", name, params);
-                                                }
+                                    let ix = InstructionType::decode(&data[..]).map_err(|e| {
+                                        anyhow::anyhow!("Failed to decode instruction: {:?}", e)
+                                    })?;
+                                    let name =
+                                        InstructionType::discrim_to_name(&data[..8]).unwrap();
+                                    match ix {
+                                        InstructionType::PlacePerpOrder(ix) => {
+                                            println!("{}, {:#?}", name, ix._params);
+                                        }
+                                        InstructionType::PlaceAndTakePerpOrder(ix) => {
+                                            println!("{}, {:#?}", name, ix._params);
+                                        }
+                                        InstructionType::PlaceOrders(ix) => {
+                                            for params in ix._params {
+                                                println!("{}, {:#?}", name, params);
                                             }
-                                            _ => {}
                                         }
+                                        _ => {}
                                     }
                                 }
                             }
@@ -90,3 +86,536 @@ fn instructions() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn enum_variant_fields() -> anyhow::Result<()> {
+    let oracle = solana_sdk::pubkey!("H5jfagEnMVNH3PMc2TU2F7tNuXE6b4zCwoL5ip1b4ZHi");
+    let mut data = vec![1u8];
+    data.extend_from_slice(oracle.as_ref());
+    data.extend_from_slice(&7u16.to_le_bytes());
+
+    match PerpFulfillmentMethod::try_from_slice(&data)? {
+        PerpFulfillmentMethod::Match(maker, index) => {
+            assert_eq!(maker, oracle);
+            assert_eq!(index, 7);
+        }
+        other => anyhow::bail!("unexpected variant: {:?}", other),
+    }
+
+    let mode = MarginCalculationMode::try_from_slice(&[0, 1])?;
+    assert!(matches!(
+        mode,
+        MarginCalculationMode::Standard {
+            track_open_orders_fraction: true
+        }
+    ));
+    Ok(())
+}
+
+#[test]
+fn error_codes() -> anyhow::Result<()> {
+    use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+    assert_eq!(
+        ErrorCode::try_from(0x1773),
+        Ok(ErrorCode::InsufficientCollateral)
+    );
+    assert_eq!(ErrorCode::try_from(1), Err(1));
+    assert_eq!(ErrorCode::InsufficientCollateral.code(), 6003);
+    assert_eq!(
+        ErrorCode::InsufficientCollateral.name(),
+        "InsufficientCollateral"
+    );
+    assert_eq!(
+        ErrorCode::InsufficientCollateral.msg(),
+        "Insufficient collateral"
+    );
+
+    let err = ProgramError::Custom(6003);
+    assert_eq!(
+        ErrorCode::from_program_error(&err),
+        Some(ErrorCode::InsufficientCollateral)
+    );
+    assert_eq!(
+        ErrorCode::from_program_error(&ProgramError::InvalidArgument),
+        None
+    );
+
+    let err = TransactionError::InstructionError(0, InstructionError::Custom(6003));
+    match err.clone() {
+        TransactionError::InstructionError(_, err) => assert_eq!(
+            ErrorCode::from_instruction_error(err),
+            Some(ErrorCode::InsufficientCollateral)
+        ),
+        other => anyhow::bail!("unexpected error: {:?}", other),
+    }
+    assert_eq!(
+        ErrorCode::from_transaction_error(&err),
+        Some(ErrorCode::InsufficientCollateral)
+    );
+    assert_eq!(
+        ErrorCode::from_transaction_error(&TransactionError::AccountInUse),
+        None
+    );
+    let err = TransactionError::InstructionError(0, InstructionError::InvalidArgument);
+    assert_eq!(ErrorCode::from_transaction_error(&err), None);
+
+    let err: anchor_lang::error::Error = ErrorCode::InsufficientCollateral.into();
+    assert_eq!(
+        ErrorCode::from_anchor_error(&err),
+        Some(ErrorCode::InsufficientCollateral)
+    );
+    Ok(())
+}
+
+#[test]
+fn ix_builders() -> anyhow::Result<()> {
+    use anchor_lang::InstructionData;
+
+    let authority = Pubkey::new_unique();
+    let ix = ix_builders::initialize_user(
+        accounts::InitializeUser {
+            user: Pubkey::new_unique(),
+            user_stats: Pubkey::new_unique(),
+            state: Pubkey::new_unique(),
+            authority,
+            payer: authority,
+            rent: solana_sdk::sysvar::rent::ID,
+            system_program: solana_sdk::system_program::ID,
+        },
+        1,
+        [7; 32],
+    );
+    assert_eq!(ix.program_id, ID);
+    assert_eq!(
+        ix.data,
+        instruction::InitializeUser {
+            _sub_account_id: 1,
+            _name: [7; 32],
+        }
+        .data()
+    );
+    assert_eq!(ix.accounts.len(), 7);
+    assert!(ix.accounts[0].is_writable && !ix.accounts[0].is_signer);
+    assert!(!ix.accounts[3].is_writable && ix.accounts[3].is_signer);
+    assert_eq!(ix.accounts[3].pubkey, authority);
+    Ok(())
+}
+
+#[test]
+fn decode_errors() -> anyhow::Result<()> {
+    let discrim = AccountType::name_to_discrim("User")?;
+    assert_eq!(AccountType::discrim_to_name(&discrim)?, "User");
+
+    assert!(matches!(
+        AccountType::decode(&discrim[..4]),
+        Err(DecodeError::TooShort { len: 4 })
+    ));
+    assert!(matches!(
+        AccountType::decode(&discrim),
+        Err(DecodeError::Borsh(_))
+    ));
+    assert!(matches!(
+        InstructionType::decode(&[0; 8]),
+        Err(DecodeError::UnknownDiscriminator(discrim)) if discrim == [0; 8]
+    ));
+    assert!(matches!(
+        EventType::name_to_discrim("Unknown"),
+        Err(DecodeError::UnknownName(name)) if name == "Unknown"
+    ));
+    Ok(())
+}
+
+#[test]
+fn decode_truncated_data() -> anyhow::Result<()> {
+    // Every prefix of valid discriminators followed by arbitrary bytes decodes without panicking.
+    let discrims = [
+        AccountType::name_to_discrim("User")?,
+        AccountType::name_to_discrim("PerpMarket")?,
+        InstructionType::name_to_discrim("PlacePerpOrder")?,
+        EventType::name_to_discrim("OrderActionRecord")?,
+    ];
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    for discrim in &discrims {
+        let mut data = discrim.clone();
+        data.extend((0..512).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        }));
+        for len in 0..=data.len() {
+            let _ = AccountType::decode(&data[..len]);
+            let _ = InstructionType::decode(&data[..len]);
+            let _ = EventType::decode(&data[..len]);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn encode_round_trip() -> anyhow::Result<()> {
+    let (authority, user, user_stats) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let referrer = ReferrerName {
+        authority,
+        user,
+        user_stats,
+        name: [7; 32],
+    };
+    let data = AccountType::ReferrerName(referrer).encode();
+    assert_eq!(data.len(), 8 + 128);
+    match AccountType::decode(&data)? {
+        AccountType::ReferrerName(decoded) => {
+            assert_eq!(decoded.authority, authority);
+            assert_eq!(decoded.user, user);
+            assert_eq!(decoded.user_stats, user_stats);
+            assert_eq!(decoded.name, [7; 32]);
+        }
+        _ => panic!("expected a referrer name"),
+    }
+
+    let ix = instruction::InitializeUser {
+        _sub_account_id: 1,
+        _name: [7; 32],
+    };
+    let data = InstructionType::InitializeUser(ix).encode();
+    assert_eq!(
+        data,
+        anchor_lang::InstructionData::data(&instruction::InitializeUser {
+            _sub_account_id: 1,
+            _name: [7; 32],
+        })
+    );
+    match InstructionType::decode(&data)? {
+        InstructionType::InitializeUser(decoded) => {
+            assert_eq!(decoded._sub_account_id, 1);
+            assert_eq!(decoded._name, [7; 32]);
+        }
+        _ => panic!("expected initialize_user"),
+    }
+    Ok(())
+}
+
+#[test]
+fn docs() -> anyhow::Result<()> {
+    let source = anchor_idl::Builder::new()
+        .idl("idl.json")
+        .options()?
+        .to_generator_in(env!("CARGO_MANIFEST_DIR").as_ref())?
+        .generate_source()?;
+    assert!(source.contains(concat!(
+        "    pub struct PerpMarket {\n",
+        "        /// The perp market's address. It is a pda of the market index\n",
+        "        pub pubkey: Pubkey,\n",
+    )));
+    Ok(())
+}
+
+#[test]
+fn runtime_decoding_zero_copy() -> anyhow::Result<()> {
+    use std::collections::BTreeMap;
+
+    use anchor_idl::{parse_idl, IdlDecoder, IdlValue, StructOpts};
+
+    let loaded = parse_idl(include_str!("../idl.json"))?;
+    // The zero copy accounts given to `generate_cpi_crate!`.
+    let struct_opts = BTreeMap::from([(
+        "InsuranceFundStake".to_string(),
+        StructOpts {
+            zero_copy: true,
+            packed: false,
+        },
+    )]);
+    let decoder = IdlDecoder::from(&loaded).with_struct_opts(&struct_opts);
+
+    let stake = InsuranceFundStake {
+        authority: Pubkey::new_unique(),
+        if_shares: u128::MAX,
+        last_withdraw_request_shares: 1,
+        if_base: 2,
+        last_valid_ts: -3,
+        last_withdraw_request_value: 4,
+        last_withdraw_request_ts: 5,
+        cost_basis: -6,
+        market_index: 7,
+        padding: [0; 14],
+    };
+    let mut data = AccountType::InsuranceFundStake(stake).encode();
+    // Bytes following a zero copy account are ignored.
+    data.extend_from_slice(&[0; 16]);
+
+    let typed = match AccountType::decode(&data)? {
+        AccountType::InsuranceFundStake(typed) => typed,
+        _ => anyhow::bail!("expected an insurance fund stake"),
+    };
+    let decoded = decoder.decode_account(&data)?;
+    assert_eq!(decoded.name, "InsuranceFundStake");
+    assert_eq!(
+        decoded.value,
+        IdlValue::Struct(vec![
+            (
+                "authority".to_string(),
+                IdlValue::PublicKey(typed.authority)
+            ),
+            ("ifShares".to_string(), IdlValue::U128(typed.if_shares)),
+            (
+                "lastWithdrawRequestShares".to_string(),
+                IdlValue::U128(typed.last_withdraw_request_shares)
+            ),
+            ("ifBase".to_string(), IdlValue::U128(typed.if_base)),
+            (
+                "lastValidTs".to_string(),
+                IdlValue::I64(typed.last_valid_ts)
+            ),
+            (
+                "lastWithdrawRequestValue".to_string(),
+                IdlValue::U64(typed.last_withdraw_request_value)
+            ),
+            (
+                "lastWithdrawRequestTs".to_string(),
+                IdlValue::I64(typed.last_withdraw_request_ts)
+            ),
+            ("costBasis".to_string(), IdlValue::I64(typed.cost_basis)),
+            ("marketIndex".to_string(), IdlValue::U16(typed.market_index)),
+            (
+                "padding".to_string(),
+                IdlValue::Array(typed.padding.iter().map(|&b| IdlValue::U8(b)).collect())
+            ),
+        ])
+    );
+    assert_eq!(typed.if_shares, u128::MAX);
+    assert_eq!(typed.market_index, 7);
+    Ok(())
+}
+
+#[test]
+fn runtime_decoding() -> anyhow::Result<()> {
+    use anchor_idl::{parse_idl, IdlDecoder, IdlValue};
+
+    let loaded = parse_idl(include_str!("../idl.json"))?;
+    let decoder = IdlDecoder::from(&loaded);
+    let field = |value: &IdlValue, name: &str| match value {
+        IdlValue::Struct(fields) => fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone()),
+        _ => None,
+    };
+
+    let referrer = ReferrerName {
+        authority: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        user_stats: Pubkey::new_unique(),
+        name: [7; 32],
+    };
+    let decoded = decoder.decode_account(&AccountType::ReferrerName(referrer).encode())?;
+    assert_eq!(decoded.name, "ReferrerName");
+    assert_eq!(
+        field(&decoded.value, "userStats"),
+        Some(IdlValue::PublicKey(referrer.user_stats))
+    );
+
+    let params = OrderParams {
+        base_asset_amount: 5,
+        direction: PositionDirection::Short,
+        max_ts: Some(9),
+        ..Default::default()
+    };
+    let data =
+        InstructionType::PlacePerpOrder(instruction::PlacePerpOrder { _params: params }).encode();
+    let decoded = decoder.decode_instruction(&data)?;
+    assert_eq!(decoded.name, "placePerpOrder");
+    let params = field(&decoded.value, "params").unwrap();
+    assert_eq!(field(&params, "baseAssetAmount"), Some(IdlValue::U64(5)));
+    assert_eq!(
+        field(&params, "direction"),
+        Some(IdlValue::Enum {
+            variant: "Short".to_string(),
+            fields: None
+        })
+    );
+    assert_eq!(
+        field(&params, "maxTs"),
+        Some(IdlValue::Option(Some(Box::new(IdlValue::I64(9)))))
+    );
+
+    // Every prefix of the data is rejected rather than decoded or panicking.
+    for len in 0..data.len() {
+        assert!(decoder.decode_instruction(&data[..len]).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn runtime_encoding() -> anyhow::Result<()> {
+    use std::collections::BTreeMap;
+
+    use anchor_idl::{parse_idl, IdlEncoder};
+    use serde_json::json;
+
+    let loaded = parse_idl(include_str!("../idl.json"))?;
+    let encoder = IdlEncoder::from(&loaded);
+
+    let state = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let mut accounts = BTreeMap::from([
+        ("state".to_string(), state),
+        ("user".to_string(), user),
+        ("authority".to_string(), authority),
+    ]);
+    let mut args = json!({
+        "params": {
+            "orderType": "Limit",
+            "marketType": "Perp",
+            "direction": "Short",
+            "userOrderId": 3,
+            "baseAssetAmount": "18446744073709551615",
+            "price": 100,
+            "marketIndex": 1,
+            "reduceOnly": false,
+            "postOnly": "MustPostOnly",
+            "immediateOrCancel": false,
+            "maxTs": 9,
+            "triggerPrice": null,
+            "triggerCondition": "Above",
+            "oraclePriceOffset": -4,
+            "auctionDuration": null,
+            "auctionStartPrice": null,
+            "auctionEndPrice": null
+        }
+    });
+    let ix = encoder.encode_instruction(&ID, "placePerpOrder", &args, &accounts)?;
+
+    let expected = ix_builders::place_perp_order(
+        accounts::PlacePerpOrder {
+            state,
+            user,
+            authority,
+        },
+        OrderParams {
+            order_type: OrderType::Limit,
+            market_type: MarketType::Perp,
+            direction: PositionDirection::Short,
+            user_order_id: 3,
+            base_asset_amount: u64::MAX,
+            price: 100,
+            market_index: 1,
+            post_only: PostOnlyParam::MustPostOnly,
+            max_ts: Some(9),
+            trigger_condition: OrderTriggerCondition::Above,
+            oracle_price_offset: Some(-4),
+            ..Default::default()
+        },
+    );
+    assert_eq!(ix.program_id, expected.program_id);
+    assert_eq!(ix.accounts, expected.accounts);
+    assert_eq!(ix.data, expected.data);
+
+    args["params"]["baseAssetAmount"] = json!(-1);
+    let err = encoder
+        .encode_instruction(&ID, "placePerpOrder", &args, &accounts)
+        .unwrap_err();
+    assert_eq!(err.path, "args.params.baseAssetAmount");
+
+    accounts.remove("user");
+    let err = encoder
+        .encode_instruction(&ID, "place_perp_order", &args, &accounts)
+        .unwrap_err();
+    assert_eq!(err.path, "accounts.user");
+    Ok(())
+}
+
+#[test]
+fn serde_json_rendering() -> anyhow::Result<()> {
+    use serde_json::json;
+
+    let referrer = ReferrerName {
+        authority: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        user_stats: Pubkey::new_unique(),
+        name: [7; 32],
+    };
+    let value = serde_json::to_value(AccountType::ReferrerName(referrer))?;
+    assert_eq!(
+        value,
+        json!({
+            "type": "ReferrerName",
+            "data": {
+                "authority": referrer.authority.to_string(),
+                "user": referrer.user.to_string(),
+                "userStats": referrer.user_stats.to_string(),
+                "name": referrer.name,
+            }
+        })
+    );
+
+    let params = OrderParams {
+        base_asset_amount: u64::MAX,
+        direction: PositionDirection::Short,
+        max_ts: Some(9),
+        ..Default::default()
+    };
+    let ix = InstructionType::PlacePerpOrder(instruction::PlacePerpOrder { _params: params });
+    let value = serde_json::to_value(&ix)?;
+    assert_eq!(value["type"], "PlacePerpOrder");
+    let params = &value["data"]["params"];
+    assert_eq!(params["baseAssetAmount"], "18446744073709551615");
+    assert_eq!(params["direction"], json!({ "kind": "Short" }));
+    assert_eq!(params["maxTs"], 9);
+    assert_eq!(params["triggerPrice"], json!(null));
+
+    let decoded: InstructionType = serde_json::from_value(value.clone())?;
+    assert_eq!(decoded.encode(), ix.encode());
+
+    // The runtime encoder accepts the JSON of generated types.
+    let loaded = anchor_idl::parse_idl(include_str!("../idl.json"))?;
+    let encoder = anchor_idl::IdlEncoder::from(&loaded);
+    let place_perp_order = loaded
+        .idl
+        .instructions
+        .iter()
+        .find(|ix| ix.name == "placePerpOrder")
+        .unwrap();
+    assert_eq!(
+        encoder.encode_args(place_perp_order, &value["data"])?,
+        ix.encode()
+    );
+    let methods = [
+        PerpFulfillmentMethod::AMM(Some(u64::MAX)),
+        PerpFulfillmentMethod::Match(Pubkey::new_unique(), 7),
+    ];
+    for method in methods {
+        let value = serde_json::to_value(method)?;
+        let mut data = vec![];
+        encoder.encode_type(
+            &anchor_idl::types::IdlType::Defined("PerpFulfillmentMethod".to_string()),
+            &value,
+            "method",
+            &mut data,
+        )?;
+        assert_eq!(data, method.try_to_vec()?);
+    }
+    let mode = MarginCalculationMode::Standard {
+        track_open_orders_fraction: true,
+    };
+    let value = serde_json::to_value(mode)?;
+    assert_eq!(
+        value,
+        json!({ "kind": "Standard", "fields": { "track_open_orders_fraction": true } })
+    );
+    let mut data = vec![];
+    encoder.encode_type(
+        &anchor_idl::types::IdlType::Defined("MarginCalculationMode".to_string()),
+        &value,
+        "mode",
+        &mut data,
+    )?;
+    assert_eq!(data, mode.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,458 @@
+anchor_gen::generate_cpi_crate!("idl.json", serde);
+
+declare_id!("8GFY6BCsLxP5dvHj3a376PCqn3A5sMD4oHPX5Z2rCkrS");
+
+#[test]
+fn generics() {
+    use anchor_gen::prelude::*;
+
+    let vault = Vault {
+        owner: Pubkey::new_unique(),
+        balances: Ring {
+            items: [1, 2, 3, 4],
+            len: 4,
+        },
+        history: History {
+            ring: Ring {
+                items: [-1, 0, 1],
+                len: 3,
+            },
+        },
+        limit: 500,
+        recent: [7, -7],
+    };
+    let data = AccountType::Vault(vault).encode();
+    match AccountType::decode(&data).unwrap() {
+        AccountType::Vault(decoded) => {
+            assert_eq!(decoded.owner, vault.owner);
+            assert_eq!(decoded.balances.items, [1, 2, 3, 4]);
+            assert_eq!(decoded.history.ring.items, [-1, 0, 1]);
+        }
+        _ => panic!("expected a vault"),
+    }
+
+    let ix = InstructionType::Deposit(instruction::Deposit {
+        _amounts: Ring {
+            items: [5, 6],
+            len: 2,
+        },
+        _window: [8, 9],
+    });
+    match InstructionType::decode(&ix.encode()).unwrap() {
+        InstructionType::Deposit(decoded) => assert_eq!(decoded._amounts.items, [5, 6]),
+        _ => panic!("expected a deposit"),
+    }
+}
+
+#[test]
+fn aliases() {
+    use anchor_gen::prelude::*;
+
+    let balances: Balances = Ring {
+        items: [1, 2, 3, 4],
+        len: 4,
+    };
+    let limit: Amount = 500;
+    let recent: Window<i64> = [7, -7];
+    let vault = Vault {
+        owner: Pubkey::new_unique(),
+        balances,
+        history: History {
+            ring: Ring {
+                items: [0; 3],
+                len: 0,
+            },
+        },
+        limit,
+        recent,
+    };
+    let data = AccountType::Vault(vault).encode();
+    match AccountType::decode(&data).unwrap() {
+        AccountType::Vault(decoded) => {
+            assert_eq!(decoded.balances.items, balances.items);
+            assert_eq!(decoded.limit, limit);
+            assert_eq!(decoded.recent, recent);
+        }
+        _ => panic!("expected a vault"),
+    }
+
+    let window: Window<u64> = [8, 9];
+    let ix = InstructionType::Deposit(instruction::Deposit {
+        _amounts: Ring {
+            items: [0; 2],
+            len: 0,
+        },
+        _window: window,
+    });
+    match InstructionType::decode(&ix.encode()).unwrap() {
+        InstructionType::Deposit(decoded) => assert_eq!(decoded._window, window),
+        _ => panic!("expected a deposit"),
+    }
+}
+
+#[test]
+fn constants() {
+    assert_eq!(
+        constants::ADMIN.to_string(),
+        "Govz1VyoyLD5BL6CSCxUJLVLsQHRwjfFj1prNsdNg5Jw"
+    );
+    assert_eq!(constants::LABEL, "say \"vault\" \\ 1");
+    assert_eq!(constants::MAX_DEPOSIT, 1_000_000_000u64);
+    assert_eq!(constants::MIN_BALANCE, i128::MIN);
+    assert_eq!(constants::VAULT_SEED, b"vault");
+}
+
+#[test]
+fn unparsable_constants() {
+    let mut idl: serde_json::Value = serde_json::from_str(include_str!("../idl.json")).unwrap();
+    // `MAX_DEPOSIT`, a `u64`.
+    idl["constants"][2]["value"] = "PRICE_PRECISION * 10".into();
+    let err = anchor_idl::parse_idl(&idl.to_string()).unwrap_err();
+    assert_eq!(err.path.as_deref(), Some("constants[2].value"));
+    assert_eq!(
+        err.message,
+        "could not parse `PRICE_PRECISION * 10`: expected an integer, found `PRICE_PRECISION * 10`"
+    );
+}
+
+#[cfg(test)]
+fn generate_source() -> String {
+    anchor_idl::Builder::new()
+        .idl("idl.json")
+        .serde()
+        .options()
+        .unwrap()
+        .to_generator_in(env!("CARGO_MANIFEST_DIR").as_ref())
+        .unwrap()
+        .generate_source()
+        .unwrap()
+}
+
+#[test]
+fn docs() {
+    let source = generate_source();
+    assert!(source.contains(concat!(
+        "    /// Holds the \"balances\" of an owner.\n",
+        "    /// Paths such as `C:\\vault` keep their backslash.\n",
+        "    #[account]\n",
+    )));
+}
+
+#[test]
+fn optional_accounts() {
+    // Omitted optional accounts are given to the program as `None`.
+    fn _referrer<'a, 'info>(
+        accounts: &'a ix_accounts::Withdraw<'info>,
+    ) -> &'a Option<AccountInfo<'info>> {
+        &accounts.referrer
+    }
+
+    let (vault, owner, referrer, destination) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let withdraw = |referrer| {
+        ix_builders::withdraw(
+            accounts::Withdraw {
+                vault,
+                owner,
+                referrer,
+                destination,
+            },
+            5,
+        )
+    };
+
+    let ix = withdraw(Some(referrer));
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys, [vault, owner, referrer, destination]);
+    assert!(ix.accounts[2].is_writable);
+
+    // The program ID takes the place of an omitted account, keeping the others in order.
+    let ix = withdraw(None);
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys, [vault, owner, ID, destination]);
+    assert!(!ix.accounts[2].is_writable && !ix.accounts[2].is_signer);
+}
+
+#[test]
+fn fixed_addresses() {
+    use std::collections::BTreeMap;
+
+    let (tick, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let ix = ix_builders::cross(
+        accounts::Cross {
+            tick,
+            authority,
+            ..Default::default()
+        },
+        2,
+        vec![7],
+    );
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys, [tick, authority, anchor_lang::system_program::ID]);
+
+    let loaded = anchor_idl::parse_idl(include_str!("../idl.json")).unwrap();
+    let accounts = BTreeMap::from([
+        ("tick".to_string(), tick),
+        ("authority".to_string(), authority),
+    ]);
+    let encoded = anchor_idl::IdlEncoder::from(&loaded)
+        .encode_instruction(
+            &ID,
+            "cross",
+            &serde_json::json!({ "accounts": 2, "data": [7] }),
+            &accounts,
+        )
+        .unwrap();
+    assert_eq!(encoded, ix);
+
+    let mut idl: serde_json::Value = serde_json::from_str(include_str!("../idl.json")).unwrap();
+    idl["instructions"][2]["accounts"][2]["address"] = "system".into();
+    let err = anchor_idl::parse_idl(&idl.to_string()).unwrap_err();
+    assert_eq!(
+        err.path.as_deref(),
+        Some("instructions[2].accounts[2].address")
+    );
+    assert_eq!(err.message, "invalid public key `system`");
+}
+
+#[test]
+fn colliding_args() {
+    // `cross` takes arguments named after the parameters and locals of builders.
+    let ix = ix_builders::cross(Default::default(), 2, vec![7]);
+    assert_eq!(ix.accounts.len(), 3);
+    assert_eq!(&ix.data[8..], &[2, 1, 0, 0, 0, 7]);
+}
+
+#[test]
+fn custom_serialization() {
+    let mut idl: serde_json::Value = serde_json::from_str(include_str!("../idl.json")).unwrap();
+    let (i, _) = idl["types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .find(|(_, ty)| ty["name"] == "Tick")
+        .unwrap();
+    idl["types"][i]["serialization"] = serde_json::json!({ "custom": "ssz" });
+    let err = anchor_idl::parse_idl(&idl.to_string()).unwrap_err();
+    assert_eq!(err.path, Some(format!("types[{}].serialization", i)));
+    assert_eq!(
+        err.message,
+        "custom serialization ssz of Tick is not supported"
+    );
+}
+
+#[test]
+fn pdas() {
+    let owner = Pubkey::new_unique();
+    assert_eq!(
+        pda::withdraw::find_vault_address(&owner),
+        Pubkey::find_program_address(&[b"vault", owner.as_ref()], &ID)
+    );
+    assert_eq!(
+        pda::withdraw::find_destination_address(&owner),
+        Pubkey::find_program_address(&[owner.as_ref()], &ID)
+    );
+
+    // Rather than leaving out a seed and deriving another address, accounts with a seed of an
+    // unsupported type or of an unknown field have no helper.
+    let source = generate_source();
+    assert!(!source.contains("pub mod deposit"));
+    assert!(!source.contains("find_referrer_address"));
+}
+
+#[test]
+fn typed_accounts() {
+    use anchor_gen::prelude::*;
+    use std::collections::BTreeSet;
+
+    let (vault_key, owner, destination) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let vault = Vault {
+        owner,
+        balances: Ring {
+            items: [0; 4],
+            len: 0,
+        },
+        history: History {
+            ring: Ring {
+                items: [0; 3],
+                len: 0,
+            },
+        },
+        limit: 500,
+        recent: [0; 2],
+    };
+    let try_accounts = |vault_owner: Pubkey| {
+        let mut data = AccountType::Vault(vault).encode();
+        let mut lamports = [0u64; 4];
+        let [vault_lamports, owner_lamports, referrer_lamports, destination_lamports] =
+            &mut lamports;
+        let (mut owner_data, mut referrer_data, mut destination_data) = ([], [], []);
+        let system_program = anchor_lang::system_program::ID;
+        let infos = [
+            AccountInfo::new(
+                &vault_key,
+                false,
+                true,
+                vault_lamports,
+                &mut data,
+                &vault_owner,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &owner,
+                true,
+                false,
+                owner_lamports,
+                &mut owner_data,
+                &system_program,
+                false,
+                0,
+            ),
+            // The program ID in place of the omitted referrer.
+            AccountInfo::new(
+                &ID,
+                false,
+                false,
+                referrer_lamports,
+                &mut referrer_data,
+                &system_program,
+                true,
+                0,
+            ),
+            AccountInfo::new(
+                &destination,
+                false,
+                true,
+                destination_lamports,
+                &mut destination_data,
+                &system_program,
+                false,
+                0,
+            ),
+        ];
+        ix_accounts::Withdraw::try_accounts(
+            &ID,
+            &mut &infos[..],
+            &[],
+            &mut Default::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|accounts| (accounts.vault.limit, accounts.referrer.is_none()))
+    };
+
+    // The vault is deserialized when the accounts are checked.
+    assert_eq!(try_accounts(ID).unwrap(), (500, true));
+    // A vault owned by another program is rejected.
+    assert!(try_accounts(Pubkey::new_unique()).is_err());
+}
+
+#[test]
+fn untyped_accounts() {
+    let source = anchor_idl::Builder::new()
+        .idl("idl.json")
+        .untyped_accounts()
+        .options()
+        .unwrap()
+        .to_generator_in(env!("CARGO_MANIFEST_DIR").as_ref())
+        .unwrap()
+        .generate_source()
+        .unwrap();
+    assert!(source.contains("pub vault: AccountInfo<'info>,"));
+    assert!(!source.contains("Account<'info, Vault>"));
+    assert!(!source.contains("AccountLoader<'info, Tick>"));
+    assert!(generate_source().contains("pub vault: Box<Account<'info, Vault>>,"));
+}
+
+#[test]
+fn zero_copy() {
+    use anchor_gen::prelude::*;
+
+    fn tick<'a, 'info>(accounts: &'a ix_accounts::Cross<'info>) -> &'a AccountLoader<'info, Tick> {
+        &accounts.tick
+    }
+    let _ = tick;
+
+    let tick = Tick {
+        liquidity: 7,
+        index: -3,
+        fee: Fee {
+            rate: 30,
+            amount: 1_000,
+        },
+        initialized: 1,
+        bump: 254,
+    };
+    let data = AccountType::Tick(tick).encode();
+    assert_eq!(data.len(), 8 + 24);
+    match AccountType::decode(&data).unwrap() {
+        AccountType::Tick(decoded) => {
+            assert_eq!(decoded.index, -3);
+            assert_eq!({ decoded.fee.amount }, 1_000);
+        }
+        _ => panic!("expected a tick"),
+    }
+
+    // Account data is 8 byte aligned, as it is on chain.
+    let mut words = [0u64; 4];
+    bytemuck::cast_slice_mut(&mut words).copy_from_slice(&data);
+    let borrowed = Tick::decode_ref(bytemuck::cast_slice(&words)).unwrap();
+    assert_eq!(borrowed.liquidity, 7);
+    assert_eq!({ borrowed.fee.rate }, 30);
+}
+
+#[test]
+fn zero_copy_serde() {
+    use anchor_gen::prelude::*;
+    use serde_json::json;
+
+    let tick = Tick {
+        liquidity: u64::MAX,
+        index: -3,
+        fee: Fee {
+            rate: 30,
+            amount: 1_000,
+        },
+        initialized: 1,
+        bump: 254,
+    };
+    let value = serde_json::to_value(AccountType::Tick(tick)).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "type": "Tick",
+            "data": {
+                "liquidity": "18446744073709551615",
+                "index": -3,
+                "fee": { "rate": 30, "amount": "1000" },
+                "initialized": 1,
+                "bump": 254,
+            }
+        })
+    );
+
+    let decoded: AccountType = serde_json::from_value(value).unwrap();
+    assert_eq!(decoded.encode(), AccountType::Tick(tick).encode());
+}
+
+#[test]
+fn zero_copy_generics() {
+    let err = anchor_idl::Builder::new()
+        .idl("idl.json")
+        .zero_copy(["Ring"])
+        .options()
+        .unwrap()
+        .to_generator_in(env!("CARGO_MANIFEST_DIR").as_ref())
+        .err()
+        .unwrap();
+    assert_eq!(err.message, "zero copy struct Ring cannot be generic");
+}
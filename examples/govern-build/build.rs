@@ -0,0 +1,7 @@
+fn main() {
+    anchor_gen::Builder::new()
+        .idl("../govern-cpi/idl.json")
+        .out_file("govern.rs")
+        .emit()
+        .unwrap();
+}
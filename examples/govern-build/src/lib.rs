@@ -0,0 +1,100 @@
+#![allow(clippy::derivable_impls)]
+
+include!(concat!(env!("OUT_DIR"), "/govern.rs"));
+
+declare_id!("Govz1VyoyLD5BL6CSCxUJLVLsQHRwjfFj1prNsdNg5Jw");
+
+#[test]
+fn matches_macro_expansion() {
+    use anchor_idl::CpiCrateInput;
+
+    // The options of the macro are parsed from its invocation in govern-cpi.
+    let govern_cpi: syn::File = syn::parse_str(include_str!("../../govern-cpi/src/lib.rs"))
+        .expect("govern-cpi should parse");
+    let input = govern_cpi
+        .items
+        .iter()
+        .find_map(|item| match item {
+            syn::Item::Macro(item)
+                if item
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "generate_cpi_crate") =>
+            {
+                Some(item.mac.parse_body::<CpiCrateInput>())
+            }
+            _ => None,
+        })
+        .expect("govern-cpi should invoke generate_cpi_crate!")
+        .expect("the input of generate_cpi_crate! should parse");
+    let expanded = input
+        .to_options()
+        .unwrap()
+        .to_generator_in("../govern-cpi".as_ref())
+        .unwrap()
+        .generate_cpi_crate()
+        .unwrap();
+
+    let built: syn::File = syn::parse_str(include_str!(concat!(env!("OUT_DIR"), "/govern.rs")))
+        .expect("generated source should parse");
+    let expanded: syn::File = syn::parse2(expanded).expect("expansion should parse");
+    // Both are printed alike, as formatting adds trailing commas to the expansion.
+    assert_eq!(
+        prettyplease::unparse(&built),
+        prettyplease::unparse(&expanded)
+    );
+}
+
+#[test]
+fn interoperates_with_macro_crate() {
+    use anchor_gen::prelude::*;
+
+    assert_eq!(ID.to_string(), govern_cpi::ID.to_string());
+
+    let vote = govern_cpi::Vote {
+        proposal: Default::default(),
+        voter: Default::default(),
+        bump: 254,
+        side: 1,
+        weight: 1_000,
+    };
+    let data = govern_cpi::AccountType::Vote(vote).encode();
+    match AccountType::decode(&data).unwrap() {
+        AccountType::Vote(decoded) => {
+            assert_eq!(
+                (decoded.bump, decoded.side, decoded.weight),
+                (254, 1, 1_000)
+            )
+        }
+        _ => panic!("expected a vote"),
+    }
+
+    let ix = InstructionType::SetVote(instruction::SetVote {
+        _side: 2,
+        _weight: 7,
+    });
+    let data = ix.encode();
+    assert_eq!(
+        data,
+        govern_cpi::InstructionType::SetVote(govern_cpi::instruction::SetVote {
+            _side: 2,
+            _weight: 7,
+        })
+        .encode()
+    );
+
+    for name in ["Governor", "Proposal", "ProposalMeta", "Vote"] {
+        assert_eq!(
+            AccountType::name_to_discrim(name).unwrap(),
+            govern_cpi::AccountType::name_to_discrim(name).unwrap()
+        );
+    }
+    for name in ["ProposalCreateEvent", "VoteSetEvent"] {
+        assert_eq!(
+            EventType::name_to_discrim(name).unwrap(),
+            govern_cpi::EventType::name_to_discrim(name).unwrap()
+        );
+    }
+}
@@ -1,3 +1,170 @@
 anchor_gen::generate_cpi_crate!("idl.json");
 
 declare_id!("Govz1VyoyLD5BL6CSCxUJLVLsQHRwjfFj1prNsdNg5Jw");
+
+#[test]
+fn pdas() {
+    let proposal = Pubkey::new_unique();
+    let voter = Pubkey::new_unique();
+    let expected =
+        Pubkey::find_program_address(&[b"TribecaVote", proposal.as_ref(), voter.as_ref()], &ID);
+    assert_eq!(
+        pda::new_vote::find_vote_address(&proposal, &voter),
+        expected
+    );
+
+    let governor = Pubkey::new_unique();
+    let expected = Pubkey::find_program_address(
+        &[b"TribecaProposal", governor.as_ref(), &7u64.to_le_bytes()],
+        &ID,
+    );
+    assert_eq!(
+        pda::create_proposal::find_proposal_address(&governor, 7),
+        expected
+    );
+}
+
+#[test]
+fn typed_accounts() {
+    // The proposal created by the instruction stays untyped, so that it can be passed before it
+    // is initialized, while the existing governor is deserialized.
+    fn proposal<'a, 'info>(
+        accounts: &'a ix_accounts::CreateProposal<'info>,
+    ) -> &'a AccountInfo<'info> {
+        &accounts.proposal
+    }
+    fn governor<'a, 'info>(
+        accounts: &'a ix_accounts::CreateProposal<'info>,
+    ) -> &'a Account<'info, Governor> {
+        &accounts.governor
+    }
+    fn vote<'a, 'info>(accounts: &'a ix_accounts::SetVote<'info>) -> &'a Account<'info, Vote> {
+        &accounts.vote
+    }
+    let _ = (proposal, governor, vote);
+}
+
+#[test]
+fn encode_round_trip() {
+    use anchor_gen::prelude::*;
+
+    let vote = Vote {
+        proposal: Pubkey::new_unique(),
+        voter: Pubkey::new_unique(),
+        bump: 254,
+        side: 1,
+        weight: 1_000,
+    };
+    let data = AccountType::Vote(vote).encode();
+    assert_eq!(
+        &data[..8],
+        &AccountType::name_to_discrim("Vote").unwrap()[..]
+    );
+    match AccountType::decode(&data).unwrap() {
+        AccountType::Vote(decoded) => {
+            assert_eq!(decoded.proposal, vote.proposal);
+            assert_eq!(decoded.voter, vote.voter);
+            assert_eq!(
+                (decoded.bump, decoded.side, decoded.weight),
+                (254, 1, 1_000)
+            );
+        }
+        _ => panic!("expected a vote"),
+    }
+
+    let ix = InstructionType::SetVote(instruction::SetVote {
+        _side: 2,
+        _weight: 7,
+    });
+    let data = ix.encode();
+    assert_eq!(
+        data,
+        anchor_lang::InstructionData::data(&instruction::SetVote {
+            _side: 2,
+            _weight: 7,
+        })
+    );
+    match InstructionType::decode(&data).unwrap() {
+        InstructionType::SetVote(decoded) => assert_eq!((decoded._side, decoded._weight), (2, 7)),
+        _ => panic!("expected set_vote"),
+    }
+
+    let event = EventType::VoteSetEvent(VoteSetEvent {
+        governor: Pubkey::new_unique(),
+        proposal: vote.proposal,
+        voter: vote.voter,
+        vote: Pubkey::new_unique(),
+        side: 1,
+        weight: 1_000,
+    });
+    let data = event.encode();
+    assert_eq!(EventType::decode(&data).unwrap().encode(), data);
+}
+
+#[test]
+fn events_from_logs() {
+    use anchor_gen::prelude::*;
+
+    let event = |side: u8| {
+        let data = EventType::VoteSetEvent(VoteSetEvent {
+            governor: Pubkey::new_unique(),
+            proposal: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+            vote: Pubkey::new_unique(),
+            side,
+            weight: 1,
+        })
+        .encode();
+        format!("Program data: {}", base64::encode(data))
+    };
+    let caller = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", ID),
+        "Program log: Instruction: SetVote".to_string(),
+        event(1),
+        format!("Program {} consumed 5000 of 200000 compute units", ID),
+        format!("Program {} success", ID),
+        format!("Program {} invoke [1]", caller),
+        event(2),
+        format!("Program {} invoke [2]", ID),
+        event(3),
+        format!("Program {} failed: custom program error: 0x1", ID),
+        format!("Program {} success", caller),
+    ];
+
+    let events = EventType::from_logs(&logs)
+        .map(|event| match event.unwrap() {
+            LogEvent {
+                event: EventType::VoteSetEvent(event),
+                depth,
+            } => (event.side, depth),
+            _ => panic!("expected a vote set event"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(events, vec![(1, 1), (3, 2)]);
+}
+
+#[test]
+fn cpi_events() {
+    use anchor_gen::prelude::*;
+
+    let event = EventType::ProposalActivateEvent(ProposalActivateEvent {
+        governor: Pubkey::new_unique(),
+        proposal: Pubkey::new_unique(),
+        voting_ends_at: 1_700_000_000,
+    })
+    .encode();
+    let mut data = anchor_gen::EVENT_IX_TAG_LE.to_vec();
+    data.extend_from_slice(&event);
+
+    assert!(is_cpi_event(&data));
+    assert!(InstructionType::decode(&data).is_err());
+    match EventType::decode_cpi_event(&data).unwrap() {
+        EventType::ProposalActivateEvent(event) => assert_eq!(event.voting_ends_at, 1_700_000_000),
+        _ => panic!("expected a proposal activate event"),
+    }
+
+    let ix = InstructionType::ActivateProposal(instruction::ActivateProposal {}).encode();
+    assert!(!is_cpi_event(&ix));
+    assert!(InstructionType::decode(&ix).is_ok());
+}
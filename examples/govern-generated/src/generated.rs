@@ -0,0 +1,763 @@
+// Generated from the IDL of govern v0.5.6 by anchor-gen v0.3.4. Do not edit.
+
+use anchor_lang::prelude::*;
+pub mod typedefs {
+    //! User-defined types.
+    use super::*;
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+    #[derive(Copy)]
+    #[derive(Debug)]
+    #[derive(Default)]
+    pub struct GovernanceParameters {
+        pub voting_delay: u64,
+        pub voting_period: u64,
+        pub quorum_votes: u64,
+        pub timelock_delay_seconds: i64,
+    }
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+    #[derive(Debug)]
+    pub struct ProposalInstruction {
+        pub program_id: Pubkey,
+        pub keys: Vec<ProposalAccountMeta>,
+        pub data: Vec<u8>,
+    }
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+    #[derive(Copy)]
+    #[derive(Debug)]
+    #[derive(Default)]
+    pub struct ProposalAccountMeta {
+        pub pubkey: Pubkey,
+        pub is_signer: bool,
+        pub is_writable: bool,
+    }
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    #[derive(Copy)]
+    pub enum ProposalState {
+        Draft,
+        Active,
+        Canceled,
+        Defeated,
+        Succeeded,
+        Queued,
+    }
+    impl Default for ProposalState {
+        fn default() -> Self {
+            Self::Draft
+        }
+    }
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    #[derive(Copy)]
+    pub enum VoteSide {
+        Pending,
+        Against,
+        For,
+        Abstain,
+    }
+    impl Default for VoteSide {
+        fn default() -> Self {
+            Self::Pending
+        }
+    }
+}
+pub mod state {
+    //! Structs of accounts which hold state.
+    use super::*;
+    #[account]
+    /// Account: Governor
+    #[derive(Copy)]
+    #[derive(Default)]
+    pub struct Governor {
+        pub base: Pubkey,
+        pub bump: u8,
+        pub proposal_count: u64,
+        pub electorate: Pubkey,
+        pub smart_wallet: Pubkey,
+        pub params: GovernanceParameters,
+    }
+    #[account]
+    /// Account: Proposal
+    #[derive(Default)]
+    pub struct Proposal {
+        pub governor: Pubkey,
+        pub index: u64,
+        pub bump: u8,
+        pub proposer: Pubkey,
+        pub quorum_votes: u64,
+        pub for_votes: u64,
+        pub against_votes: u64,
+        pub abstain_votes: u64,
+        pub canceled_at: i64,
+        pub created_at: i64,
+        pub activated_at: i64,
+        pub voting_ends_at: i64,
+        pub queued_at: i64,
+        pub queued_transaction: Pubkey,
+        pub instructions: Vec<ProposalInstruction>,
+    }
+    #[account]
+    /// Account: ProposalMeta
+    #[derive(Default)]
+    pub struct ProposalMeta {
+        pub proposal: Pubkey,
+        pub title: String,
+        pub description_link: String,
+    }
+    #[account]
+    /// Account: Vote
+    #[derive(Copy)]
+    #[derive(Default)]
+    pub struct Vote {
+        pub proposal: Pubkey,
+        pub voter: Pubkey,
+        pub bump: u8,
+        pub side: u8,
+        pub weight: u64,
+    }
+}
+pub mod ix_accounts {
+    //! Accounts used in instructions.
+    use super::*;
+    #[derive(Accounts)]
+    pub struct CreateGovernor<'info> {
+        pub base: Signer<'info>,
+        #[account(mut)]
+        pub governor: AccountInfo<'info>,
+        pub smart_wallet: AccountInfo<'info>,
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        pub system_program: AccountInfo<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct CreateProposal<'info> {
+        #[account(mut)]
+        pub governor: Box<Account<'info, Governor>>,
+        #[account(mut)]
+        pub proposal: AccountInfo<'info>,
+        pub proposer: Signer<'info>,
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        pub system_program: AccountInfo<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct ActivateProposal<'info> {
+        pub governor: Box<Account<'info, Governor>>,
+        #[account(mut)]
+        pub proposal: Box<Account<'info, Proposal>>,
+        pub electorate: Signer<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct CancelProposal<'info> {
+        pub governor: Box<Account<'info, Governor>>,
+        #[account(mut)]
+        pub proposal: Box<Account<'info, Proposal>>,
+        pub proposer: Signer<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct QueueProposal<'info> {
+        pub governor: Box<Account<'info, Governor>>,
+        #[account(mut)]
+        pub proposal: Box<Account<'info, Proposal>>,
+        #[account(mut)]
+        pub transaction: AccountInfo<'info>,
+        #[account(mut)]
+        pub smart_wallet: AccountInfo<'info>,
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        pub smart_wallet_program: AccountInfo<'info>,
+        pub system_program: AccountInfo<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct NewVote<'info> {
+        pub proposal: Box<Account<'info, Proposal>>,
+        #[account(mut)]
+        pub vote: AccountInfo<'info>,
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        pub system_program: AccountInfo<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct SetVote<'info> {
+        pub governor: Box<Account<'info, Governor>>,
+        #[account(mut)]
+        pub proposal: Box<Account<'info, Proposal>>,
+        #[account(mut)]
+        pub vote: Box<Account<'info, Vote>>,
+        pub electorate: Signer<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct SetGovernanceParams<'info> {
+        #[account(mut)]
+        pub governor: Box<Account<'info, Governor>>,
+        pub smart_wallet: Signer<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct SetElectorate<'info> {
+        #[account(mut)]
+        pub governor: Box<Account<'info, Governor>>,
+        pub smart_wallet: Signer<'info>,
+    }
+    #[derive(Accounts)]
+    pub struct CreateProposalMeta<'info> {
+        pub proposal: Box<Account<'info, Proposal>>,
+        pub proposer: Signer<'info>,
+        #[account(mut)]
+        pub proposal_meta: AccountInfo<'info>,
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        pub system_program: AccountInfo<'info>,
+    }
+}
+pub mod ix_builders {
+    //! Builders of instructions for off-chain clients.
+    use super::*;
+    /// Builds a `create_governor` instruction.
+    pub fn create_governor(
+        __accounts: accounts::CreateGovernor,
+        bump: u8,
+        electorate: Pubkey,
+        params: GovernanceParameters,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::CreateGovernor {
+            _bump: bump,
+            _electorate: electorate,
+            _params: params,
+        };
+        let mut data = vec![103u8, 30u8, 78u8, 252u8, 28u8, 128u8, 40u8, 3u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `create_proposal` instruction.
+    pub fn create_proposal(
+        __accounts: accounts::CreateProposal,
+        bump: u8,
+        instructions: Vec<ProposalInstruction>,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::CreateProposal {
+            _bump: bump,
+            _instructions: instructions,
+        };
+        let mut data = vec![132u8, 116u8, 68u8, 174u8, 216u8, 160u8, 198u8, 22u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `activate_proposal` instruction.
+    pub fn activate_proposal(
+        __accounts: accounts::ActivateProposal,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::ActivateProposal {};
+        let mut data = vec![90u8, 186u8, 203u8, 234u8, 70u8, 185u8, 191u8, 21u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `cancel_proposal` instruction.
+    pub fn cancel_proposal(
+        __accounts: accounts::CancelProposal,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::CancelProposal {};
+        let mut data = vec![106u8, 74u8, 128u8, 146u8, 19u8, 65u8, 39u8, 23u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `queue_proposal` instruction.
+    pub fn queue_proposal(
+        __accounts: accounts::QueueProposal,
+        tx_bump: u8,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::QueueProposal {
+            _tx_bump: tx_bump,
+        };
+        let mut data = vec![168u8, 219u8, 139u8, 211u8, 205u8, 152u8, 125u8, 110u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `new_vote` instruction.
+    pub fn new_vote(
+        __accounts: accounts::NewVote,
+        bump: u8,
+        voter: Pubkey,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::NewVote {
+            _bump: bump,
+            _voter: voter,
+        };
+        let mut data = vec![163u8, 108u8, 157u8, 189u8, 140u8, 80u8, 13u8, 143u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `set_vote` instruction.
+    pub fn set_vote(
+        __accounts: accounts::SetVote,
+        side: u8,
+        weight: u64,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::SetVote {
+            _side: side,
+            _weight: weight,
+        };
+        let mut data = vec![171u8, 33u8, 83u8, 172u8, 148u8, 215u8, 239u8, 97u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `set_governance_params` instruction.
+    pub fn set_governance_params(
+        __accounts: accounts::SetGovernanceParams,
+        params: GovernanceParameters,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::SetGovernanceParams {
+            _params: params,
+        };
+        let mut data = vec![175u8, 187u8, 3u8, 73u8, 8u8, 251u8, 67u8, 178u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `set_electorate` instruction.
+    pub fn set_electorate(
+        __accounts: accounts::SetElectorate,
+        new_electorate: Pubkey,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::SetElectorate {
+            _new_electorate: new_electorate,
+        };
+        let mut data = vec![145u8, 135u8, 92u8, 16u8, 77u8, 195u8, 145u8, 91u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+    /// Builds a `create_proposal_meta` instruction.
+    pub fn create_proposal_meta(
+        __accounts: accounts::CreateProposalMeta,
+        bump: u8,
+        title: String,
+        description_link: String,
+    ) -> anchor_lang::solana_program::instruction::Instruction {
+        let args = instruction::CreateProposalMeta {
+            _bump: bump,
+            _title: title,
+            _description_link: description_link,
+        };
+        let mut data = vec![238u8, 138u8, 212u8, 160u8, 46u8, 53u8, 51u8, 88u8];
+        AnchorSerialize::serialize(&args, &mut data).unwrap();
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ID,
+            accounts: __accounts.to_account_metas(None),
+            data,
+        }
+    }
+}
+pub mod pda {
+    //! Program derived addresses of instruction accounts.
+    use super::*;
+    /// Program derived addresses of accounts of the `create_governor` instruction.
+    pub mod create_governor {
+        use super::*;
+        /// Finds the address of the `governor` account.
+        pub fn find_governor_address(base: &Pubkey) -> (Pubkey, u8) {
+            find_governor_address_with_program_id(base, &ID)
+        }
+        /// Finds the address of the `governor` account as derived by `program_id`.
+        pub fn find_governor_address_with_program_id(
+            base: &Pubkey,
+            program_id: &Pubkey,
+        ) -> (Pubkey, u8) {
+            Pubkey::find_program_address(
+                &[&b"TribecaGovernor"[..], base.as_ref()],
+                program_id,
+            )
+        }
+    }
+    /// Program derived addresses of accounts of the `create_proposal` instruction.
+    pub mod create_proposal {
+        use super::*;
+        /// Finds the address of the `proposal` account.
+        pub fn find_proposal_address(
+            governor: &Pubkey,
+            governor_proposal_count: u64,
+        ) -> (Pubkey, u8) {
+            find_proposal_address_with_program_id(governor, governor_proposal_count, &ID)
+        }
+        /// Finds the address of the `proposal` account as derived by `program_id`.
+        pub fn find_proposal_address_with_program_id(
+            governor: &Pubkey,
+            governor_proposal_count: u64,
+            program_id: &Pubkey,
+        ) -> (Pubkey, u8) {
+            Pubkey::find_program_address(
+                &[
+                    &b"TribecaProposal"[..],
+                    governor.as_ref(),
+                    governor_proposal_count.to_le_bytes().as_ref(),
+                ],
+                program_id,
+            )
+        }
+    }
+    /// Program derived addresses of accounts of the `new_vote` instruction.
+    pub mod new_vote {
+        use super::*;
+        /// Finds the address of the `vote` account.
+        pub fn find_vote_address(proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+            find_vote_address_with_program_id(proposal, voter, &ID)
+        }
+        /// Finds the address of the `vote` account as derived by `program_id`.
+        pub fn find_vote_address_with_program_id(
+            proposal: &Pubkey,
+            voter: &Pubkey,
+            program_id: &Pubkey,
+        ) -> (Pubkey, u8) {
+            Pubkey::find_program_address(
+                &[&b"TribecaVote"[..], proposal.as_ref(), voter.as_ref()],
+                program_id,
+            )
+        }
+    }
+    /// Program derived addresses of accounts of the `create_proposal_meta` instruction.
+    pub mod create_proposal_meta {
+        use super::*;
+        /// Finds the address of the `proposal_meta` account.
+        pub fn find_proposal_meta_address(proposal: &Pubkey) -> (Pubkey, u8) {
+            find_proposal_meta_address_with_program_id(proposal, &ID)
+        }
+        /// Finds the address of the `proposal_meta` account as derived by `program_id`.
+        pub fn find_proposal_meta_address_with_program_id(
+            proposal: &Pubkey,
+            program_id: &Pubkey,
+        ) -> (Pubkey, u8) {
+            Pubkey::find_program_address(
+                &[&b"TribecaProposalMeta"[..], proposal.as_ref()],
+                program_id,
+            )
+        }
+    }
+}
+pub mod events {
+    //! Events emitted by the program.
+    use super::*;
+    #[event]
+    pub struct GovernorCreateEvent {
+        pub governor: Pubkey,
+        pub electorate: Pubkey,
+        pub smart_wallet: Pubkey,
+        pub parameters: GovernanceParameters,
+    }
+    #[event]
+    pub struct ProposalCreateEvent {
+        pub governor: Pubkey,
+        pub proposal: Pubkey,
+        pub index: u64,
+        pub instructions: Vec<ProposalInstruction>,
+    }
+    #[event]
+    pub struct ProposalActivateEvent {
+        pub governor: Pubkey,
+        pub proposal: Pubkey,
+        pub voting_ends_at: i64,
+    }
+    #[event]
+    pub struct ProposalCancelEvent {
+        pub governor: Pubkey,
+        pub proposal: Pubkey,
+    }
+    #[event]
+    pub struct ProposalQueueEvent {
+        pub governor: Pubkey,
+        pub proposal: Pubkey,
+        pub transaction: Pubkey,
+    }
+    #[event]
+    pub struct VoteSetEvent {
+        pub governor: Pubkey,
+        pub proposal: Pubkey,
+        pub voter: Pubkey,
+        pub vote: Pubkey,
+        pub side: u8,
+        pub weight: u64,
+    }
+    #[event]
+    pub struct ProposalMetaCreateEvent {
+        pub governor: Pubkey,
+        pub proposal: Pubkey,
+        pub title: String,
+        pub description_link: String,
+    }
+    #[event]
+    pub struct GovernorSetParamsEvent {
+        pub governor: Pubkey,
+        pub prev_params: GovernanceParameters,
+        pub params: GovernanceParameters,
+    }
+    #[event]
+    pub struct GovernorSetElectorateEvent {
+        pub governor: Pubkey,
+        pub prev_electorate: Pubkey,
+        pub new_electorate: Pubkey,
+    }
+}
+pub use events::*;
+pub mod errors {
+    //! Errors returned by the program.
+    use super::*;
+    /// Errors returned by the program.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[repr(u32)]
+    pub enum ErrorCode {
+        /// Invalid vote side.
+        InvalidVoteSide = 6000u32,
+        /// The owner of the smart wallet doesn't match with current.
+        GovernorNotFound = 6001u32,
+        /// The proposal cannot be activated since it has not yet passed the voting delay.
+        VotingDelayNotMet = 6002u32,
+        /// Only drafts can be canceled.
+        ProposalNotDraft = 6003u32,
+        /// The proposal must be active.
+        ProposalNotActive = 6004u32,
+    }
+    impl ErrorCode {
+        /// Code of the error, as found in `ProgramError::Custom`.
+        pub fn code(&self) -> u32 {
+            *self as u32
+        }
+        /// Name of the error in the IDL.
+        pub fn name(&self) -> &'static str {
+            match self {
+                Self::InvalidVoteSide => "InvalidVoteSide",
+                Self::GovernorNotFound => "GovernorNotFound",
+                Self::VotingDelayNotMet => "VotingDelayNotMet",
+                Self::ProposalNotDraft => "ProposalNotDraft",
+                Self::ProposalNotActive => "ProposalNotActive",
+            }
+        }
+        /// Message of the error, falling back to its name if the IDL has no message.
+        pub fn msg(&self) -> &'static str {
+            match self {
+                Self::InvalidVoteSide => "Invalid vote side.",
+                Self::GovernorNotFound => {
+                    "The owner of the smart wallet doesn't match with current."
+                }
+                Self::VotingDelayNotMet => {
+                    "The proposal cannot be activated since it has not yet passed the voting delay."
+                }
+                Self::ProposalNotDraft => "Only drafts can be canceled.",
+                Self::ProposalNotActive => "The proposal must be active.",
+            }
+        }
+        /// Maps a `ProgramError::Custom` returned by the program to its error.
+        pub fn from_program_error(err: &ProgramError) -> Option<Self> {
+            match err {
+                ProgramError::Custom(code) => Self::try_from(*code).ok(),
+                _ => None,
+            }
+        }
+        /// Maps an `InstructionError::Custom` returned by the program to its error.
+        ///
+        /// Failed transactions report these as `TransactionError::InstructionError`.
+        pub fn from_instruction_error<E>(err: E) -> Option<Self>
+        where
+            ProgramError: TryFrom<E>,
+        {
+            ProgramError::try_from(err)
+                .ok()
+                .and_then(|err| Self::from_program_error(&err))
+        }
+        /// Maps a `TransactionError::InstructionError` of a failed transaction to the error
+        /// of its `InstructionError::Custom`.
+        ///
+        /// Takes the `TransactionError` of `solana_sdk`, matched by its `serde` form.
+        pub fn from_transaction_error<E: anchor_gen::serde::Serialize>(
+            err: &E,
+        ) -> Option<Self> {
+            anchor_gen::transaction_error_code(err)
+                .and_then(|code| Self::try_from(code).ok())
+        }
+        /// Maps an Anchor error returned by the program to its error.
+        pub fn from_anchor_error(err: &anchor_lang::error::Error) -> Option<Self> {
+            match err {
+                anchor_lang::error::Error::AnchorError(err) => {
+                    Self::try_from(err.error_code_number).ok()
+                }
+                anchor_lang::error::Error::ProgramError(err) => {
+                    Self::from_program_error(&err.program_error)
+                }
+            }
+        }
+    }
+    impl TryFrom<u32> for ErrorCode {
+        type Error = u32;
+        fn try_from(code: u32) -> std::result::Result<Self, u32> {
+            match code {
+                6000u32 => Ok(Self::InvalidVoteSide),
+                6001u32 => Ok(Self::GovernorNotFound),
+                6002u32 => Ok(Self::VotingDelayNotMet),
+                6003u32 => Ok(Self::ProposalNotDraft),
+                6004u32 => Ok(Self::ProposalNotActive),
+                _ => Err(code),
+            }
+        }
+    }
+    impl From<ErrorCode> for u32 {
+        fn from(err: ErrorCode) -> u32 {
+            err.code()
+        }
+    }
+    impl From<ErrorCode> for ProgramError {
+        fn from(err: ErrorCode) -> ProgramError {
+            ProgramError::Custom(err.code())
+        }
+    }
+    impl From<ErrorCode> for anchor_lang::error::Error {
+        fn from(err: ErrorCode) -> anchor_lang::error::Error {
+            anchor_lang::error::Error::from(anchor_lang::error::AnchorError {
+                error_name: err.name().to_string(),
+                error_code_number: err.code(),
+                error_msg: err.msg().to_string(),
+                error_origin: None,
+                compared_values: None,
+            })
+        }
+    }
+    impl std::fmt::Display for ErrorCode {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.msg())
+        }
+    }
+    impl std::error::Error for ErrorCode {}
+}
+pub use errors::ErrorCode;
+use ix_accounts::*;
+pub use state::*;
+pub use typedefs::*;
+#[program]
+pub mod govern {
+    //! Anchor CPI crate generated from govern v0.5.6 using [anchor-gen](https://crates.io/crates/anchor-gen) v0.3.4.
+    use super::*;
+    pub fn create_governor(
+        _ctx: Context<CreateGovernor>,
+        _bump: u8,
+        _electorate: Pubkey,
+        _params: GovernanceParameters,
+    ) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn create_proposal(
+        _ctx: Context<CreateProposal>,
+        _bump: u8,
+        _instructions: Vec<ProposalInstruction>,
+    ) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn activate_proposal(_ctx: Context<ActivateProposal>) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn cancel_proposal(_ctx: Context<CancelProposal>) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn queue_proposal(_ctx: Context<QueueProposal>, _tx_bump: u8) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn new_vote(_ctx: Context<NewVote>, _bump: u8, _voter: Pubkey) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn set_vote(_ctx: Context<SetVote>, _side: u8, _weight: u64) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn set_governance_params(
+        _ctx: Context<SetGovernanceParams>,
+        _params: GovernanceParameters,
+    ) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn set_electorate(
+        _ctx: Context<SetElectorate>,
+        _new_electorate: Pubkey,
+    ) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+    pub fn create_proposal_meta(
+        _ctx: Context<CreateProposalMeta>,
+        _bump: u8,
+        _title: String,
+        _description_link: String,
+    ) -> Result<()> {
+        unimplemented!("This program is a wrapper for CPI.")
+    }
+}
+anchor_gen::derive_event_type!(
+    pub enum EventType { GovernorCreateEvent(GovernorCreateEvent) = [117u8, 24u8, 15u8,
+    85u8, 39u8, 58u8, 62u8, 23u8], ProposalCreateEvent(ProposalCreateEvent) = [121u8,
+    18u8, 213u8, 155u8, 223u8, 158u8, 95u8, 70u8],
+    ProposalActivateEvent(ProposalActivateEvent) = [247u8, 53u8, 166u8, 250u8, 118u8,
+    62u8, 53u8, 80u8], ProposalCancelEvent(ProposalCancelEvent) = [24u8, 49u8, 11u8,
+    182u8, 23u8, 59u8, 122u8, 220u8], ProposalQueueEvent(ProposalQueueEvent) = [48u8,
+    219u8, 123u8, 209u8, 140u8, 210u8, 248u8, 14u8], VoteSetEvent(VoteSetEvent) = [175u8,
+    119u8, 30u8, 108u8, 176u8, 233u8, 151u8, 252u8],
+    ProposalMetaCreateEvent(ProposalMetaCreateEvent) = [50u8, 59u8, 195u8, 75u8, 85u8,
+    227u8, 187u8, 82u8], GovernorSetParamsEvent(GovernorSetParamsEvent) = [169u8, 129u8,
+    187u8, 152u8, 130u8, 17u8, 81u8, 157u8],
+    GovernorSetElectorateEvent(GovernorSetElectorateEvent) = [146u8, 179u8, 141u8, 206u8,
+    235u8, 125u8, 146u8, 26u8], }
+);
+impl EventType {
+    /// Decodes the events emitted by this program in the log messages of a
+    /// transaction, along with the depth of the invocation which emitted them.
+    pub fn from_logs<L: AsRef<str>>(
+        logs: &[L],
+    ) -> anchor_gen::LogEvents<'_, L, EventType> {
+        anchor_gen::LogEvents::new(&ID, logs)
+    }
+}
+anchor_gen::derive_account_type!(
+    pub enum AccountType { Governor(Governor) = [37u8, 136u8, 44u8, 80u8, 68u8, 85u8,
+    213u8, 178u8], Proposal(Proposal) = [26u8, 94u8, 189u8, 187u8, 116u8, 136u8, 53u8,
+    33u8], ProposalMeta(ProposalMeta) = [50u8, 100u8, 46u8, 24u8, 151u8, 174u8, 216u8,
+    78u8], Vote(Vote) = [96u8, 91u8, 104u8, 57u8, 145u8, 35u8, 172u8, 155u8], }
+);
+anchor_gen::derive_instruction_type!(
+    pub enum InstructionType { CreateGovernor(instruction::CreateGovernor) = [103u8,
+    30u8, 78u8, 252u8, 28u8, 128u8, 40u8, 3u8],
+    CreateProposal(instruction::CreateProposal) = [132u8, 116u8, 68u8, 174u8, 216u8,
+    160u8, 198u8, 22u8], ActivateProposal(instruction::ActivateProposal) = [90u8, 186u8,
+    203u8, 234u8, 70u8, 185u8, 191u8, 21u8], CancelProposal(instruction::CancelProposal)
+    = [106u8, 74u8, 128u8, 146u8, 19u8, 65u8, 39u8, 23u8],
+    QueueProposal(instruction::QueueProposal) = [168u8, 219u8, 139u8, 211u8, 205u8,
+    152u8, 125u8, 110u8], NewVote(instruction::NewVote) = [163u8, 108u8, 157u8, 189u8,
+    140u8, 80u8, 13u8, 143u8], SetVote(instruction::SetVote) = [171u8, 33u8, 83u8, 172u8,
+    148u8, 215u8, 239u8, 97u8], SetGovernanceParams(instruction::SetGovernanceParams) =
+    [175u8, 187u8, 3u8, 73u8, 8u8, 251u8, 67u8, 178u8],
+    SetElectorate(instruction::SetElectorate) = [145u8, 135u8, 92u8, 16u8, 77u8, 195u8,
+    145u8, 91u8], CreateProposalMeta(instruction::CreateProposalMeta) = [238u8, 138u8,
+    212u8, 160u8, 46u8, 53u8, 51u8, 88u8], }
+);
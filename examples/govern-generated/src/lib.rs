@@ -0,0 +1,22 @@
+#![allow(clippy::derivable_impls)]
+
+include!("generated.rs");
+
+declare_id!("Govz1VyoyLD5BL6CSCxUJLVLsQHRwjfFj1prNsdNg5Jw");
+
+#[test]
+fn generated_types() {
+    use anchor_gen::prelude::*;
+
+    let vote = Vote {
+        proposal: Pubkey::new_unique(),
+        voter: Pubkey::new_unique(),
+        bump: 254,
+        side: 1,
+        weight: 1_000,
+    };
+    match AccountType::decode(&AccountType::Vote(vote).encode()).unwrap() {
+        AccountType::Vote(decoded) => assert_eq!(decoded.weight, 1_000),
+        _ => panic!("expected a vote"),
+    }
+}
@@ -0,0 +1,160 @@
+anchor_gen::generate_cpi_crate!("idl.json");
+
+declare_id!("Govz1VyoyLD5BL6CSCxUJLVLsQHRwjfFj1prNsdNg5Jw");
+
+#[test]
+fn address() {
+    let loaded = anchor_idl::parse_idl(include_str!("../idl.json")).unwrap();
+    assert_eq!(loaded.format, anchor_idl::IdlFormat::Spec);
+    assert_eq!(
+        loaded.idl.metadata.unwrap()["address"],
+        ID.to_string().as_str()
+    );
+}
+
+#[test]
+fn discriminators() {
+    use anchor_gen::prelude::*;
+
+    for name in ["Governor", "Proposal", "ProposalMeta", "Vote"] {
+        assert_eq!(
+            AccountType::name_to_discrim(name).unwrap(),
+            govern_cpi::AccountType::name_to_discrim(name).unwrap()
+        );
+    }
+    for name in ["CreateGovernor", "CreateProposal", "NewVote", "SetVote"] {
+        assert_eq!(
+            InstructionType::name_to_discrim(name).unwrap(),
+            govern_cpi::InstructionType::name_to_discrim(name).unwrap()
+        );
+    }
+    for name in ["ProposalCreateEvent", "VoteSetEvent"] {
+        assert_eq!(
+            EventType::name_to_discrim(name).unwrap(),
+            govern_cpi::EventType::name_to_discrim(name).unwrap()
+        );
+    }
+}
+
+#[test]
+fn accounts_and_types() {
+    use anchor_gen::prelude::*;
+
+    // `Governor` and `GovernanceParameters` are both defined in `types`; only the former is listed
+    // in `accounts`.
+    let governor = Governor {
+        base: Pubkey::new_unique(),
+        bump: 255,
+        proposal_count: 3,
+        electorate: Pubkey::new_unique(),
+        smart_wallet: Pubkey::new_unique(),
+        params: typedefs::GovernanceParameters {
+            voting_delay: 1,
+            voting_period: 2,
+            quorum_votes: 3,
+            timelock_delay_seconds: 4,
+        },
+    };
+    let data = AccountType::Governor(governor).encode();
+    match govern_cpi::AccountType::decode(&data).unwrap() {
+        govern_cpi::AccountType::Governor(decoded) => {
+            assert_eq!(decoded.base.to_string(), governor.base.to_string());
+            assert_eq!(decoded.proposal_count, 3);
+            assert_eq!(
+                (
+                    decoded.params.voting_delay,
+                    decoded.params.voting_period,
+                    decoded.params.quorum_votes,
+                    decoded.params.timelock_delay_seconds,
+                ),
+                (1, 2, 3, 4)
+            );
+        }
+        _ => panic!("expected a governor"),
+    }
+    assert_eq!(
+        Governor::try_deserialize(&mut &data[..])
+            .unwrap()
+            .proposal_count,
+        3
+    );
+
+    assert_eq!(
+        InstructionType::SetVote(instruction::SetVote {
+            _side: 2,
+            _weight: 7,
+        })
+        .encode(),
+        govern_cpi::InstructionType::SetVote(govern_cpi::instruction::SetVote {
+            _side: 2,
+            _weight: 7,
+        })
+        .encode()
+    );
+}
+
+#[test]
+fn events() {
+    use anchor_gen::prelude::*;
+
+    let (voter, vote) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let data = EventType::VoteSetEvent(VoteSetEvent {
+        governor: Pubkey::new_unique(),
+        proposal: Pubkey::new_unique(),
+        voter,
+        vote,
+        side: 1,
+        weight: 1_000,
+    })
+    .encode();
+    match govern_cpi::EventType::decode(&data).unwrap() {
+        govern_cpi::EventType::VoteSetEvent(decoded) => {
+            assert_eq!(decoded.voter.to_string(), voter.to_string());
+            assert_eq!((decoded.side, decoded.weight), (1, 1_000));
+        }
+        _ => panic!("expected a vote set event"),
+    }
+    match EventType::decode(&data).unwrap() {
+        EventType::VoteSetEvent(decoded) => assert_eq!(decoded.vote, vote),
+        _ => panic!("expected a vote set event"),
+    }
+}
+
+#[test]
+fn errors_point_into_the_idl() {
+    let idl: serde_json::Value = serde_json::from_str(include_str!("../idl.json")).unwrap();
+    let error_path = |edit: &dyn Fn(&mut serde_json::Value)| {
+        let mut idl = idl.clone();
+        edit(&mut idl);
+        anchor_idl::parse_idl(&idl.to_string())
+            .unwrap_err()
+            .path
+            .unwrap()
+    };
+
+    // `Governor` is an account, and `ProposalCreateEvent` an event, but both are defined in
+    // `types`.
+    assert_eq!(
+        error_path(
+            &|idl| idl["types"][1]["type"]["fields"][5]["type"]["defined"]["name"] =
+                "Params".into()
+        ),
+        "types[1].type.fields[5].type.defined.name"
+    );
+    assert_eq!(
+        error_path(
+            &|idl| idl["types"][9]["type"]["fields"][3]["type"]["vec"]["defined"]["name"] =
+                "Ix".into()
+        ),
+        "types[9].type.fields[3].type.vec.defined.name"
+    );
+    assert_eq!(
+        error_path(&|idl| idl["types"][14]["type"]["variants"] = serde_json::json!([])),
+        "types[14].type.variants"
+    );
+    // `[163]` prefixes the discriminator of `new_vote`.
+    assert_eq!(
+        error_path(&|idl| idl["instructions"][6]["discriminator"] = serde_json::json!([163])),
+        "instructions[6].discriminator"
+    );
+}